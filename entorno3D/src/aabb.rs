@@ -0,0 +1,91 @@
+// aabb.rs
+// Caja alineada a los ejes, usada como volumen envolvente en el BVH
+use nalgebra_glm::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    // Caja vacía: cualquier unión con ella devuelve la otra caja
+    pub fn empty() -> Self {
+        Aabb {
+            min: Vec3::repeat(f32::INFINITY),
+            max: Vec3::repeat(f32::NEG_INFINITY),
+        }
+    }
+
+    // Para primitivas sin límites (por ejemplo un plano)
+    pub fn infinite() -> Self {
+        Aabb {
+            min: Vec3::repeat(f32::NEG_INFINITY),
+            max: Vec3::repeat(f32::INFINITY),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.min
+            .iter()
+            .chain(self.max.iter())
+            .all(|v| v.is_finite())
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    pub fn grow(&self, point: &Vec3) -> Aabb {
+        Aabb {
+            min: self.min.inf(point),
+            max: self.max.sup(point),
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    // Eje (0: x, 1: y, 2: z) en el que la caja es más larga
+    pub fn longest_axis(&self) -> usize {
+        let size = self.max - self.min;
+        if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Prueba de slabs. `inv_dir` es 1/dirección, precalculado por el llamador.
+    /// Devuelve la distancia de entrada si el rayo toca la caja antes de `t_max`.
+    pub fn hit(&self, origin: &Vec3, inv_dir: &Vec3, t_max: f32) -> Option<f32> {
+        let mut t_enter = 0.0_f32;
+        let mut t_exit = t_max;
+
+        for axis in 0..3 {
+            let mut t0 = (self.min[axis] - origin[axis]) * inv_dir[axis];
+            let mut t1 = (self.max[axis] - origin[axis]) * inv_dir[axis];
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            t_enter = t_enter.max(t0);
+            t_exit = t_exit.min(t1);
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+
+        Some(t_enter)
+    }
+}
@@ -0,0 +1,57 @@
+// accumulator.rs
+// Render progresivo: mientras la cámara y la escena no cambian, cada cuadro
+// agrega una pasada (`samples_per_pixel` muestras por píxel) y se muestra el
+// promedio acumulado.
+use crate::framebuffer::Framebuffer;
+use crate::postprocess::PostProcess;
+use crate::raytracer::RenderSettings;
+use crate::render_target::RenderTarget;
+use crate::scene::Scene;
+
+pub struct Accumulator {
+    pub width: usize,
+    pub height: usize,
+    target: RenderTarget,
+}
+
+impl Accumulator {
+    pub fn new(width: usize, height: usize) -> Self {
+        Accumulator {
+            width,
+            height,
+            target: RenderTarget::new(width, height),
+        }
+    }
+
+    // Descarta lo acumulado (llamar cuando la cámara o la escena cambian)
+    pub fn reset(&mut self) {
+        self.target.clear();
+    }
+
+    pub fn passes(&self) -> u32 {
+        self.target.passes
+    }
+
+    // Color acumulado y canales de geometría de la vista actual
+    pub fn target(&self) -> &RenderTarget {
+        &self.target
+    }
+
+    /// Renderiza una pasada más y la suma al promedio.
+    /// La semilla de `settings` se desplaza con cada pasada para que el
+    /// ruido (jitter de reflexiones y de la posición en el píxel) cambie
+    /// entre pasadas y se promedie: así los bordes se suavizan con el tiempo.
+    pub fn add_pass(&mut self, scene: &Scene, settings: &RenderSettings) {
+        let pass_settings = RenderSettings {
+            seed: settings.seed.wrapping_add(self.target.passes as u64),
+            jitter: true,
+            ..*settings
+        };
+        self.target.add_pass(scene, &pass_settings);
+    }
+
+    /// Escribe el promedio actual en el framebuffer (del mismo tamaño)
+    pub fn resolve(&self, framebuffer: &mut Framebuffer, post: &PostProcess) {
+        self.target.resolve(framebuffer, post);
+    }
+}
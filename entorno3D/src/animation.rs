@@ -0,0 +1,196 @@
+// animation.rs
+// Animación de cámara por keyframes: en cada instante se interpola la posición,
+// el punto mirado, el vector arriba y el FOV entre los keyframes vecinos.
+use crate::camera::{Camera, Projection};
+use nalgebra_glm::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKeyframe {
+    pub time: f32, // segundos
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub up: Vec3,
+    pub fov: f32, // grados; solo afecta a perspectiva y ojo de pez
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Tramos rectos entre keyframes: cambia de dirección de golpe en cada uno
+    Linear,
+    /// Catmull-Rom: pasa por todos los keyframes con velocidad continua
+    #[default]
+    Spline,
+}
+
+pub struct CameraAnimation {
+    keyframes: Vec<CameraKeyframe>,
+    interpolation: Interpolation,
+    // Ciclo cerrado: el último keyframe repite el primero y marca el fin del período
+    looped: bool,
+}
+
+impl CameraAnimation {
+    /// Los keyframes se ordenan por tiempo; hace falta al menos uno.
+    /// Con `looped`, el último keyframe cierra el ciclo: tiene que repetir la
+    /// pose del primero y su tiempo es el largo del período.
+    pub fn new(
+        mut keyframes: Vec<CameraKeyframe>,
+        interpolation: Interpolation,
+        looped: bool,
+    ) -> Result<Self, String> {
+        if keyframes.is_empty() {
+            return Err("la animación necesita al menos un keyframe".to_string());
+        }
+        if keyframes.iter().any(|k| !k.time.is_finite()) {
+            return Err("tiempo de keyframe inválido".to_string());
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        if looped {
+            let first = &keyframes[0];
+            let last = &keyframes[keyframes.len() - 1];
+            if last.time <= first.time {
+                return Err(
+                    "una animación en loop necesita keyframes en al menos dos tiempos distintos"
+                        .to_string(),
+                );
+            }
+            if (last.eye, last.center, last.up, last.fov)
+                != (first.eye, first.center, first.up, first.fov)
+            {
+                return Err(
+                    "en una animación en loop el último keyframe debe repetir la pose del primero"
+                        .to_string(),
+                );
+            }
+        }
+
+        Ok(CameraAnimation {
+            keyframes,
+            interpolation,
+            looped,
+        })
+    }
+
+    pub fn start(&self) -> f32 {
+        self.keyframes[0].time
+    }
+
+    pub fn end(&self) -> f32 {
+        self.keyframes[self.keyframes.len() - 1].time
+    }
+
+    /// Tiempo del cuadro `frame` de `frames`, repartidos del primer al último
+    /// keyframe (ambos incluidos). En loop el final se excluye: es el mismo
+    /// cuadro que el inicio, y así la secuencia se puede repetir sin saltos.
+    pub fn frame_time(&self, frame: usize, frames: usize) -> f32 {
+        let duration = self.end() - self.start();
+        if self.looped {
+            return self.start() + duration * frame as f32 / frames.max(1) as f32;
+        }
+        if frames <= 1 {
+            return self.start();
+        }
+        self.start() + duration * frame as f32 / (frames - 1) as f32
+    }
+
+    /// Cámara en el instante `time`; fuera del rango se queda en el extremo,
+    /// o vuelve a empezar si la animación es un loop
+    pub fn sample(&self, time: f32) -> CameraKeyframe {
+        let last = self.keyframes.len() - 1;
+        let time = if self.looped {
+            self.start() + (time - self.start()).rem_euclid(self.end() - self.start())
+        } else {
+            time.clamp(self.start(), self.end())
+        };
+        // Tramo [i, i + 1] que contiene a `time`
+        let i = self.keyframes[..last]
+            .iter()
+            .rposition(|k| k.time <= time)
+            .unwrap_or(0);
+        if i == last {
+            return self.keyframes[last];
+        }
+
+        let k1 = &self.keyframes[i];
+        let k2 = &self.keyframes[i + 1];
+        let span = k2.time - k1.time;
+        let t = if span > 0.0 {
+            (time - k1.time) / span
+        } else {
+            0.0
+        };
+
+        let (eye, center, up, fov) = match self.interpolation {
+            Interpolation::Linear => (
+                k1.eye.lerp(&k2.eye, t),
+                k1.center.lerp(&k2.center, t),
+                k1.up.lerp(&k2.up, t),
+                k1.fov + (k2.fov - k1.fov) * t,
+            ),
+            Interpolation::Spline => {
+                // En los extremos se repite el keyframe como vecino; en loop
+                // se toma el del otro lado de la costura (el primero y el
+                // último son la misma pose), así la velocidad no cambia ahí
+                let (k0, k3) = if self.looped {
+                    let previous = if i == 0 { last - 1 } else { i - 1 };
+                    let next = if i + 2 > last { i + 2 - last } else { i + 2 };
+                    (&self.keyframes[previous], &self.keyframes[next])
+                } else {
+                    (
+                        &self.keyframes[i.saturating_sub(1)],
+                        &self.keyframes[(i + 2).min(last)],
+                    )
+                };
+                (
+                    catmull_rom(&k0.eye, &k1.eye, &k2.eye, &k3.eye, t),
+                    catmull_rom(&k0.center, &k1.center, &k2.center, &k3.center, t),
+                    catmull_rom(&k0.up, &k1.up, &k2.up, &k3.up, t),
+                    catmull_rom_scalar(k0.fov, k1.fov, k2.fov, k3.fov, t),
+                )
+            }
+        };
+
+        let up = if up.magnitude_squared() > 0.0 {
+            up.normalize()
+        } else {
+            k1.up
+        };
+        CameraKeyframe {
+            time,
+            eye,
+            center,
+            up,
+            fov,
+        }
+    }
+
+    /// Coloca la cámara como en el instante `time`
+    pub fn apply(&self, time: f32, camera: &mut Camera) {
+        let key = self.sample(time);
+        camera.eye = key.eye;
+        camera.center = key.center;
+        camera.up = key.up;
+        match &mut camera.projection {
+            Projection::Perspective { fov } | Projection::Fisheye { fov } => *fov = key.fov,
+            _ => {}
+        }
+    }
+}
+
+fn catmull_rom(p0: &Vec3, p1: &Vec3, p2: &Vec3, p3: &Vec3, t: f32) -> Vec3 {
+    Vec3::new(
+        catmull_rom_scalar(p0.x, p1.x, p2.x, p3.x, t),
+        catmull_rom_scalar(p0.y, p1.y, p2.y, p3.y, t),
+        catmull_rom_scalar(p0.z, p1.z, p2.z, p3.z, t),
+    )
+}
+
+fn catmull_rom_scalar(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
@@ -0,0 +1,166 @@
+// brdf.rs
+// Modelo de microfacetas Cook-Torrance con distribución GGX (Trowbridge-Reitz),
+// geometría de Smith (Schlick-GGX) y Fresnel de Schlick.
+use crate::color::LinearColor;
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// Rugosidad mínima: con 0 la distribución GGX se vuelve una delta
+const MIN_ROUGHNESS: f32 = 0.03;
+
+/// Reflectancia a incidencia normal de un dieléctrico con índice `ior` (en aire)
+pub fn f0_from_ior(ior: f32) -> f32 {
+    let r = (ior - 1.0) / (ior + 1.0);
+    r * r
+}
+
+/// Fracción de la luz que se refleja al pasar de un medio a otro (Schlick).
+/// `cos_i`: coseno del ángulo de incidencia; `eta`: n del medio de origen / n del destino.
+/// Con reflexión interna total devuelve 1.
+pub fn fresnel_dielectric(cos_i: f32, eta: f32) -> f32 {
+    let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    // Saliendo hacia un medio menos denso, Schlick usa el ángulo transmitido
+    let cos = if eta > 1.0 {
+        (1.0 - sin2_t).sqrt()
+    } else {
+        cos_i
+    };
+    let r0 = f0_from_ior(eta);
+    r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+}
+
+/// Color especular a incidencia normal: los metales tiñen el reflejo con su color
+pub fn base_reflectance(base_color: LinearColor, metallic: f32, ior: f32) -> LinearColor {
+    let dielectric = f0_from_ior(ior);
+    let dielectric = LinearColor::new(dielectric, dielectric, dielectric);
+    dielectric * (1.0 - metallic) + base_color * metallic
+}
+
+pub fn fresnel_schlick(cos_theta: f32, f0: LinearColor) -> LinearColor {
+    let k = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    f0 * (1.0 - k) + LinearColor::new(1.0, 1.0, 1.0) * k
+}
+
+/// Fresnel para la luz que llega del entorno: las superficies rugosas
+/// no alcanzan reflejo total en los bordes
+pub fn fresnel_schlick_roughness(cos_theta: f32, f0: LinearColor, roughness: f32) -> LinearColor {
+    let k = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    let edge = 1.0 - roughness;
+    LinearColor::new(
+        f0.r + (edge.max(f0.r) - f0.r) * k,
+        f0.g + (edge.max(f0.g) - f0.g) * k,
+        f0.b + (edge.max(f0.b) - f0.b) * k,
+    )
+}
+
+// Rugosidad "perceptual" -> parámetro alfa de GGX
+fn alpha(roughness: f32) -> f32 {
+    let r = roughness.clamp(MIN_ROUGHNESS, 1.0);
+    r * r
+}
+
+fn ggx_distribution(n_dot_h: f32, alpha: f32) -> f32 {
+    let a2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+fn smith_geometry(n_dot_v: f32, n_dot_l: f32, alpha: f32) -> f32 {
+    let k = alpha / 2.0;
+    let g1 = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k);
+    g1(n_dot_v) * g1(n_dot_l)
+}
+
+/// BRDF multiplicada por cos(θ_l): cuánto de la luz que llega desde `light_dir`
+/// sale hacia `view_dir`. Las tres direcciones son unitarias y apuntan hacia afuera.
+pub fn cook_torrance(
+    normal: &Vec3,
+    view_dir: &Vec3,
+    light_dir: &Vec3,
+    base_color: LinearColor,
+    metallic: f32,
+    roughness: f32,
+    ior: f32,
+) -> LinearColor {
+    let n_dot_l = normal.dot(light_dir);
+    let n_dot_v = normal.dot(view_dir);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return LinearColor::black();
+    }
+
+    let half = (view_dir + light_dir).normalize();
+    let n_dot_h = normal.dot(&half).max(0.0);
+    let v_dot_h = view_dir.dot(&half).max(0.0);
+
+    let a = alpha(roughness);
+    let fresnel = fresnel_schlick(v_dot_h, base_reflectance(base_color, metallic, ior));
+    let specular = fresnel
+        * (ggx_distribution(n_dot_h, a) * smith_geometry(n_dot_v, n_dot_l, a)
+            / (4.0 * n_dot_v * n_dot_l));
+
+    // Lo que no se refleja en la superficie entra y se difunde (salvo en metales)
+    let transmitted = LinearColor::new(1.0 - fresnel.r, 1.0 - fresnel.g, 1.0 - fresnel.b);
+    let diffuse = transmitted * base_color * ((1.0 - metallic) / PI);
+
+    (diffuse + specular) * n_dot_l
+}
+
+/// Dirección con densidad proporcional a cos(θ) alrededor de `normal`
+/// (muestreo por importancia de una superficie lambertiana)
+pub fn sample_cosine_hemisphere(normal: &Vec3, xi: (f32, f32)) -> Vec3 {
+    let phi = 2.0 * PI * xi.0;
+    let r = xi.1.sqrt();
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    (tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + normal * (1.0 - xi.1).sqrt())
+        .normalize()
+}
+
+// Base ortonormal cualquiera alrededor de la normal
+fn orthonormal_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let helper = if normal.x.abs() > 0.9 {
+        Vec3::y()
+    } else {
+        Vec3::x()
+    };
+    let tangent = normal.cross(&helper).normalize();
+    (tangent, normal.cross(&tangent))
+}
+
+/// Normal de microfaceta muestreada según GGX alrededor de `normal`.
+/// `xi` son dos números uniformes en [0, 1).
+pub fn sample_ggx_normal(normal: &Vec3, roughness: f32, xi: (f32, f32)) -> Vec3 {
+    let a = alpha(roughness);
+    let phi = 2.0 * PI * xi.0;
+    let cos_theta = ((1.0 - xi.1) / (1.0 + (a * a - 1.0) * xi.1)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    (tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + normal * cos_theta)
+        .normalize()
+}
+
+/// Peso (BRDF · cos / pdf) de una dirección `light_dir` obtenida reflejando
+/// `view_dir` en una normal de microfaceta `half` muestreada con `sample_ggx_normal`.
+/// Los términos D se cancelan con la densidad de muestreo.
+pub fn ggx_sample_weight(
+    normal: &Vec3,
+    view_dir: &Vec3,
+    light_dir: &Vec3,
+    half: &Vec3,
+    f0: LinearColor,
+    roughness: f32,
+) -> LinearColor {
+    let n_dot_l = normal.dot(light_dir);
+    let n_dot_v = normal.dot(view_dir);
+    let n_dot_h = normal.dot(half);
+    let v_dot_h = view_dir.dot(half);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 || n_dot_h <= 0.0 || v_dot_h <= 0.0 {
+        return LinearColor::black();
+    }
+
+    let g = smith_geometry(n_dot_v, n_dot_l, alpha(roughness));
+    fresnel_schlick(v_dot_h, f0) * (g * v_dot_h / (n_dot_v * n_dot_h))
+}
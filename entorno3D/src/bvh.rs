@@ -0,0 +1,271 @@
+// bvh.rs
+// Jerarquía de volúmenes envolventes (BVH) sobre objetos RayIntersect.
+// Reemplaza el recorrido lineal de la escena en las consultas de
+// impacto más cercano (cast_ray) y de oclusión (cast_shadow).
+use crate::aabb::Aabb;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+// Cantidad máxima de objetos en una hoja
+const MAX_LEAF_SIZE: usize = 2;
+
+enum BvhNode {
+    Leaf {
+        bounds: Aabb,
+        start: usize,
+        count: usize,
+    },
+    Interior {
+        bounds: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl BvhNode {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { bounds, .. } | BvhNode::Interior { bounds, .. } => bounds,
+        }
+    }
+}
+
+pub struct Bvh<T: RayIntersect = Box<dyn RayIntersect>> {
+    objects: Vec<T>,
+    // Índices de objetos con caja finita, en el orden de las hojas
+    order: Vec<usize>,
+    // Objetos sin límites (planos), se prueban siempre de forma lineal
+    unbounded: Vec<usize>,
+    nodes: Vec<BvhNode>,
+}
+
+impl<T: RayIntersect> Bvh<T> {
+    pub fn new(objects: Vec<T>) -> Self {
+        let boxes: Vec<Aabb> = objects.iter().map(|o| o.bounding_box()).collect();
+
+        let (mut order, unbounded): (Vec<usize>, Vec<usize>) =
+            (0..objects.len()).partition(|&i| boxes[i].is_finite());
+
+        let mut nodes = Vec::new();
+        if !order.is_empty() {
+            let count = order.len();
+            build(&boxes, &mut order, 0, count, &mut nodes);
+        }
+
+        Bvh {
+            objects,
+            order,
+            unbounded,
+            nodes,
+        }
+    }
+
+    pub fn objects(&self) -> &[T] {
+        &self.objects
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Impacto más cercano a lo largo del rayo
+    pub fn closest_hit(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.closest_hit_object(ray_origin, ray_direction).0
+    }
+
+    /// Como `closest_hit`, junto con el índice en `objects()` del objeto golpeado
+    pub fn closest_hit_object(
+        &self,
+        ray_origin: &Vec3,
+        ray_direction: &Vec3,
+    ) -> (Intersect, Option<usize>) {
+        let mut closest = Intersect::empty();
+        let mut closest_index = None;
+        let mut zbuffer = f32::INFINITY;
+
+        let mut test = |index: usize, zbuffer: &mut f32| {
+            let i = self.objects[index].ray_intersect(ray_origin, ray_direction);
+            if i.is_intersecting && i.distance < *zbuffer {
+                *zbuffer = i.distance;
+                closest = i;
+                closest_index = Some(index);
+            }
+        };
+
+        for &index in &self.unbounded {
+            test(index, &mut zbuffer);
+        }
+
+        if self.nodes.is_empty() {
+            return (closest, closest_index);
+        }
+
+        let inv_dir = ray_direction.map(|d| 1.0 / d);
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            match &self.nodes[node_index] {
+                BvhNode::Leaf {
+                    bounds,
+                    start,
+                    count,
+                } => {
+                    if bounds.hit(ray_origin, &inv_dir, zbuffer).is_some() {
+                        for &index in &self.order[*start..*start + *count] {
+                            test(index, &mut zbuffer);
+                        }
+                    }
+                }
+                BvhNode::Interior {
+                    bounds,
+                    left,
+                    right,
+                } => {
+                    if bounds.hit(ray_origin, &inv_dir, zbuffer).is_none() {
+                        continue;
+                    }
+                    // Visitamos primero el hijo más cercano para recortar antes el zbuffer
+                    let t_left = self.nodes[*left]
+                        .bounds()
+                        .hit(ray_origin, &inv_dir, zbuffer);
+                    let t_right = self.nodes[*right]
+                        .bounds()
+                        .hit(ray_origin, &inv_dir, zbuffer);
+                    match (t_left, t_right) {
+                        (Some(tl), Some(tr)) if tl <= tr => {
+                            stack.push(*right);
+                            stack.push(*left);
+                        }
+                        (Some(_), Some(_)) => {
+                            stack.push(*left);
+                            stack.push(*right);
+                        }
+                        (Some(_), None) => stack.push(*left),
+                        (None, Some(_)) => stack.push(*right),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+
+        (closest, closest_index)
+    }
+
+    /// Primer impacto encontrado antes de `max_distance` (no necesariamente el más cercano).
+    /// Suficiente para saber si algo bloquea la luz.
+    pub fn any_hit(
+        &self,
+        ray_origin: &Vec3,
+        ray_direction: &Vec3,
+        max_distance: f32,
+    ) -> Option<Intersect> {
+        let test = |index: usize| {
+            let i = self.objects[index].ray_intersect(ray_origin, ray_direction);
+            (i.is_intersecting && i.distance < max_distance).then_some(i)
+        };
+
+        if let Some(hit) = self.unbounded.iter().find_map(|&index| test(index)) {
+            return Some(hit);
+        }
+
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let inv_dir = ray_direction.map(|d| 1.0 / d);
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node
+                .bounds()
+                .hit(ray_origin, &inv_dir, max_distance)
+                .is_none()
+            {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf { start, count, .. } => {
+                    let leaf = &self.order[*start..*start + *count];
+                    if let Some(hit) = leaf.iter().find_map(|&index| test(index)) {
+                        return Some(hit);
+                    }
+                }
+                BvhNode::Interior { left, right, .. } => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// Construye recursivamente el subárbol para order[start..start + count]
+// y devuelve el índice de su nodo raíz.
+fn build(
+    boxes: &[Aabb],
+    order: &mut [usize],
+    start: usize,
+    count: usize,
+    nodes: &mut Vec<BvhNode>,
+) -> usize {
+    let slice = &mut order[start..start + count];
+    let bounds = slice
+        .iter()
+        .fold(Aabb::empty(), |acc, &i| acc.union(&boxes[i]));
+
+    if count <= MAX_LEAF_SIZE {
+        nodes.push(BvhNode::Leaf {
+            bounds,
+            start,
+            count,
+        });
+        return nodes.len() - 1;
+    }
+
+    // División por la mediana de los centroides en el eje más largo
+    let centroid_bounds = slice
+        .iter()
+        .fold(Aabb::empty(), |acc, &i| acc.grow(&boxes[i].centroid()));
+    let axis = centroid_bounds.longest_axis();
+    slice.sort_by(|&a, &b| boxes[a].centroid()[axis].total_cmp(&boxes[b].centroid()[axis]));
+
+    // Reservamos el lugar del nodo interior antes de construir los hijos
+    let node_index = nodes.len();
+    nodes.push(BvhNode::Leaf {
+        bounds,
+        start,
+        count,
+    });
+
+    let half = count / 2;
+    let left = build(boxes, order, start, half, nodes);
+    let right = build(boxes, order, start + half, count - half, nodes);
+    nodes[node_index] = BvhNode::Interior {
+        bounds,
+        left,
+        right,
+    };
+
+    node_index
+}
+
+impl<T: RayIntersect> RayIntersect for Bvh<T> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.closest_hit(ray_origin, ray_direction)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        if !self.unbounded.is_empty() {
+            return Aabb::infinite();
+        }
+        self.nodes
+            .first()
+            .map(|node| *node.bounds())
+            .unwrap_or_else(Aabb::empty)
+    }
+}
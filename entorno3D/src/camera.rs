@@ -1,28 +1,239 @@
 //camera.rs
+use crate::ray_intersect::RayCone;
 use nalgebra_glm::Vec3;
+use rand::Rng;
+use rand::rngs::SmallRng;
 use std::f32::consts::PI;
 
 pub struct Camera {
     pub eye: Vec3,    // Camera position in world space
     pub center: Vec3, // Point the camera is looking at
     pub up: Vec3,     // Up vector
+    // Radio de la lente; con 0 es una cámara pinhole (todo enfocado)
+    pub aperture: f32,
+    // Distancia al plano de enfoque; sin valor se enfoca en `center`
+    pub focus_distance: Option<f32>,
+    pub projection: Projection,
+}
+
+/// Campo de visión vertical (grados) de la perspectiva por defecto. Es el que
+/// el render usaba antes de fijo: tan(PI / 3 * 0.33) de semialtura.
+pub const DEFAULT_FOV: f32 = 39.6;
+
+/// Cómo se convierte un punto de la pantalla en un rayo de cámara
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// `fov`: ángulo vertical en grados
+    Perspective { fov: f32 },
+    /// Rayos paralelos a la dirección de la cámara. `height` es la altura
+    /// visible en unidades de la escena; sin valor, la que cubre la
+    /// perspectiva por defecto a la distancia de `center` (y así sigue al zoom)
+    Orthographic { height: Option<f32> },
+    /// Ortográfica mirando en una de las cuatro diagonales isométricas
+    /// (elevación de 35.26°), la más cercana a la posición de `eye`
+    Isometric { height: Option<f32> },
+    /// Ojo de pez equidistante: `fov` (grados) cubre el círculo inscrito en la imagen
+    Fisheye { fov: f32 },
+    /// Panorama 360° x 180° con -Z en el centro, igual que los entornos equirectangulares
+    Equirectangular,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective { fov: DEFAULT_FOV }
+    }
 }
 
 impl Camera {
     pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
-        Camera { eye, center, up }
+        Camera {
+            eye,
+            center,
+            up,
+            aperture: 0.0,
+            focus_distance: None,
+            projection: Projection::default(),
+        }
     }
 
-    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn with_lens(mut self, aperture: f32, focus_distance: Option<f32>) -> Self {
+        self.aperture = aperture.max(0.0);
+        self.focus_distance = focus_distance;
+        self
+    }
+
+    /// Distancia (medida sobre el eje de la cámara) a la que los objetos salen nítidos.
+    /// Por defecto es la del punto alrededor del que se orbita, así el enfoque
+    /// sigue al centro al hacer orbit o zoom.
+    pub fn focus_distance(&self) -> f32 {
+        self.focus_distance
+            .unwrap_or_else(|| (self.center - self.eye).magnitude())
+    }
+
+    /// Rayo de cámara (origen, dirección) para una dirección ya en coordenadas
+    /// del mundo. Con apertura, el origen se toma al azar en la lente y el rayo
+    /// se dirige al punto donde el rayo pinhole cruza el plano de enfoque.
+    pub fn lens_ray(&self, direction: &Vec3, rng: &mut SmallRng) -> (Vec3, Vec3) {
+        if self.aperture <= 0.0 {
+            return (self.eye, *direction);
+        }
+
         let forward = (self.center - self.eye).normalize();
         let right = forward.cross(&self.up).normalize();
         let up = right.cross(&forward).normalize();
 
+        // El plano de enfoque es perpendicular al eje, no una esfera alrededor del ojo
+        let along_axis = direction.dot(&forward);
+        if along_axis <= 0.0 {
+            return (self.eye, *direction);
+        }
+        let focus_point = self.eye + direction * (self.focus_distance() / along_axis);
+
+        // Punto uniforme en el disco de la lente
+        let r = self.aperture * rng.random::<f32>().sqrt();
+        let phi = 2.0 * PI * rng.random::<f32>();
+        let origin = self.eye + right * (r * phi.cos()) + up * (r * phi.sin());
+
+        (origin, (focus_point - origin).normalize())
+    }
+
+    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
+        let forward = (self.center - self.eye).normalize();
+        let (right, up) = self.screen_axes(&forward);
+
         let rotated = vector.x * right + vector.y * up - vector.z * forward;
 
         rotated.normalize()
     }
 
+    // Ejes derecha y arriba de la pantalla para una dirección de vista
+    fn screen_axes(&self, forward: &Vec3) -> (Vec3, Vec3) {
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(forward).normalize();
+        (right, up)
+    }
+
+    /// Rayo primario (origen, dirección) por el punto (`screen_x`, `screen_y`)
+    /// de la pantalla, ambos en [-1, 1] con y hacia arriba; `aspect_ratio` es
+    /// ancho / alto. Devuelve None fuera del círculo del ojo de pez.
+    pub fn primary_ray(
+        &self,
+        screen_x: f32,
+        screen_y: f32,
+        aspect_ratio: f32,
+        rng: &mut SmallRng,
+    ) -> Option<(Vec3, Vec3)> {
+        match self.projection {
+            Projection::Perspective { fov } => {
+                let scale = (fov.to_radians() * 0.5).tan();
+                let direction = Vec3::new(screen_x * aspect_ratio * scale, screen_y * scale, -1.0);
+                Some(self.lens_ray(&self.basis_change(&direction), rng))
+            }
+            Projection::Orthographic { height } => {
+                let forward = (self.center - self.eye).normalize();
+                let (right, up) = self.screen_axes(&forward);
+                let half_height = 0.5 * self.view_height(height);
+                let origin = self.eye
+                    + right * (screen_x * aspect_ratio * half_height)
+                    + up * (screen_y * half_height);
+                Some((origin, forward))
+            }
+            Projection::Isometric { height } => {
+                let offset = self.eye - self.center;
+                let diagonal = Vec3::new(
+                    if offset.x < 0.0 { -1.0 } else { 1.0 },
+                    1.0,
+                    if offset.z < 0.0 { -1.0 } else { 1.0 },
+                )
+                .normalize();
+                let forward = -diagonal;
+                let (right, up) = self.screen_axes(&forward);
+                let half_height = 0.5 * self.view_height(height);
+                // El plano de los orígenes queda a la misma distancia del centro que el ojo
+                let origin = self.center
+                    + diagonal * offset.magnitude()
+                    + right * (screen_x * aspect_ratio * half_height)
+                    + up * (screen_y * half_height);
+                Some((origin, forward))
+            }
+            Projection::Fisheye { fov } => {
+                // El círculo se ajusta al lado corto de la imagen
+                let (px, py) = if aspect_ratio >= 1.0 {
+                    (screen_x * aspect_ratio, screen_y)
+                } else {
+                    (screen_x, screen_y / aspect_ratio)
+                };
+                let r = (px * px + py * py).sqrt();
+                if r > 1.0 {
+                    return None;
+                }
+                let theta = r * fov.to_radians() * 0.5;
+                let direction = if r > 0.0 {
+                    let s = theta.sin() / r;
+                    Vec3::new(px * s, py * s, -theta.cos())
+                } else {
+                    Vec3::new(0.0, 0.0, -1.0)
+                };
+                Some((self.eye, self.basis_change(&direction)))
+            }
+            Projection::Equirectangular => {
+                let longitude = screen_x * PI;
+                let latitude = screen_y * PI * 0.5;
+                let direction = Vec3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    -latitude.cos() * longitude.cos(),
+                );
+                Some((self.eye, self.basis_change(&direction)))
+            }
+        }
+    }
+
+    /// Cono de los rayos primarios de una imagen de `width` x `height` con
+    /// `samples_per_pixel` muestras: cada muestra cubre una fracción del píxel,
+    /// así que con más muestras el cono se angosta.
+    pub fn pixel_cone(&self, width: usize, height: usize, samples_per_pixel: u32) -> RayCone {
+        let height = height.max(1) as f32;
+        let cone = match self.projection {
+            Projection::Perspective { fov } => RayCone {
+                width: 0.0,
+                spread: 2.0 * (fov.to_radians() * 0.5).tan() / height,
+            },
+            Projection::Orthographic { height: view } | Projection::Isometric { height: view } => {
+                RayCone {
+                    width: self.view_height(view) / height,
+                    spread: 0.0,
+                }
+            }
+            Projection::Fisheye { fov } => RayCone {
+                width: 0.0,
+                spread: fov.to_radians() / height.min(width.max(1) as f32),
+            },
+            Projection::Equirectangular => RayCone {
+                width: 0.0,
+                spread: PI / height,
+            },
+        };
+        let samples = (samples_per_pixel.max(1) as f32).sqrt();
+        RayCone {
+            width: cone.width / samples,
+            spread: cone.spread / samples,
+        }
+    }
+
+    // Altura visible de las proyecciones paralelas
+    fn view_height(&self, height: Option<f32>) -> f32 {
+        height.unwrap_or_else(|| {
+            let distance = (self.center - self.eye).magnitude();
+            2.0 * distance * (DEFAULT_FOV.to_radians() * 0.5).tan()
+        })
+    }
+
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
         // Calculate the vector from the center to the eye (radius vector) and measure the distance
         let radius_vector = self.eye - self.center;
@@ -73,4 +284,32 @@ impl Camera {
         let dir = radius_vector.normalize();
         self.eye = self.center + dir * new_radius;
     }
+
+    // Modo vuelo: mueve ojo y centro juntos. `forward` va en la dirección de la
+    // vista, `right` hacia la derecha de la pantalla y `up` sobre el eje Y.
+    pub fn fly(&mut self, forward: f32, right: f32, up: f32) {
+        let view = (self.center - self.eye).normalize();
+        let side = view.cross(&self.up).normalize();
+        let offset = view * forward + side * right + Vec3::y() * up;
+        self.eye += offset;
+        self.center += offset;
+    }
+
+    // Gira la vista desde el ojo (primera persona): lo contrario de orbit, aquí
+    // se mueve `center` alrededor de `eye` a la misma distancia
+    pub fn look(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        let view = self.center - self.eye;
+        let distance = view.magnitude();
+
+        let yaw = view.z.atan2(view.x) + delta_yaw;
+        let horizontal = (view.x * view.x + view.z * view.z).sqrt();
+        let pitch = (view.y.atan2(horizontal) + delta_pitch).clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
+
+        self.center = self.eye
+            + Vec3::new(
+                distance * yaw.cos() * pitch.cos(),
+                distance * pitch.sin(),
+                distance * yaw.sin() * pitch.cos(),
+            );
+    }
 }
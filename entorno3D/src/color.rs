@@ -1,5 +1,6 @@
 //color.rs
 use std::fmt;
+use std::sync::LazyLock;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -23,13 +24,13 @@ impl Color {
     }
 
     // Function to return the color as a hex value
-    pub fn to_hex(&self) -> u32 {
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 }
 
 // Implement addition for Color
-use std::ops::Add;
+use std::ops::{Add, AddAssign};
 
 impl Add for Color {
     type Output = Color;
@@ -64,3 +65,120 @@ impl fmt::Display for Color {
         write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
     }
 }
+
+/// Color lineal en punto flotante para toda la matemática de sombreado.
+/// Los canales no se recortan: pueden superar 1.0 (HDR) hasta que el
+/// resultado pasa por `PostProcess` (que termina en `to_color()`) al escribir
+/// el framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl LinearColor {
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        LinearColor { r, g, b }
+    }
+
+    // Luminancia relativa (pesos de Rec. 709)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn black() -> Self {
+        LinearColor::new(0.0, 0.0, 0.0)
+    }
+
+    // Canales de 8 bits tal cual, sin decodificar sRGB: para texturas que
+    // guardan datos y no colores (normal maps, mapas de alturas)
+    pub fn from_raw(color: Color) -> Self {
+        LinearColor {
+            r: color.r as f32 / 255.0,
+            g: color.g as f32 / 255.0,
+            b: color.b as f32 / 255.0,
+        }
+    }
+
+    // Convierte a 8 bits recortando a [0, 1] (sin codificar: ver PostProcess)
+    pub fn to_color(self) -> Color {
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(to_u8(self.r), to_u8(self.g), to_u8(self.b))
+    }
+}
+
+// Los colores de 8 bits (escenas, texturas, luces) están en sRGB: se
+// decodifican a lineal con una tabla para no llamar a powf en cada impacto
+impl From<Color> for LinearColor {
+    fn from(color: Color) -> Self {
+        static SRGB_TO_LINEAR: LazyLock<[f32; 256]> = LazyLock::new(|| {
+            std::array::from_fn(|i| {
+                let v = i as f32 / 255.0;
+                if v <= 0.04045 {
+                    v / 12.92
+                } else {
+                    ((v + 0.055) / 1.055).powf(2.4)
+                }
+            })
+        });
+        LinearColor {
+            r: SRGB_TO_LINEAR[color.r as usize],
+            g: SRGB_TO_LINEAR[color.g as usize],
+            b: SRGB_TO_LINEAR[color.b as usize],
+        }
+    }
+}
+
+impl Add for LinearColor {
+    type Output = LinearColor;
+
+    fn add(self, other: LinearColor) -> LinearColor {
+        LinearColor {
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+        }
+    }
+}
+
+impl AddAssign for LinearColor {
+    fn add_assign(&mut self, other: LinearColor) {
+        *self = *self + other;
+    }
+}
+
+impl Mul<f32> for LinearColor {
+    type Output = LinearColor;
+
+    fn mul(self, scalar: f32) -> LinearColor {
+        LinearColor {
+            r: self.r * scalar,
+            g: self.g * scalar,
+            b: self.b * scalar,
+        }
+    }
+}
+
+// Producto componente a componente (p. ej. color de la luz por color de la superficie)
+impl Mul<LinearColor> for LinearColor {
+    type Output = LinearColor;
+
+    fn mul(self, other: LinearColor) -> LinearColor {
+        LinearColor {
+            r: self.r * other.r,
+            g: self.g * other.g,
+            b: self.b * other.b,
+        }
+    }
+}
+
+impl fmt::Display for LinearColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "LinearColor(r: {:.3}, g: {:.3}, b: {:.3})",
+            self.r, self.g, self.b
+        )
+    }
+}
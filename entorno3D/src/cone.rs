@@ -1,7 +1,9 @@
+use crate::aabb::Aabb;
 use crate::material::Material;
 use crate::ray_intersect::{Intersect, RayIntersect};
-use crate::texture::Texture;
-use nalgebra_glm::{Vec3, dot};
+use crate::texture::{self, Texture};
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
 
 pub struct ConeSection {
     pub apex: Vec3,  // vértice superior
@@ -9,16 +11,13 @@ pub struct ConeSection {
     pub r1: f32,     // radio inferior
     pub r2: f32,     // radio superior
     pub material: Material,
-    pub texture: Option<Texture>,
+    pub texture: Option<Arc<Texture>>,
 }
 
 impl RayIntersect for ConeSection {
     fn ray_intersect(&self, ray_origin: &Vec3, ray_dir: &Vec3) -> Intersect {
         // El cono truncado es la interpolación entre r1 y r2
         // Fórmula: r(y) = r1 + (r2 - r1) * ( (y - y0) / h )
-        let y0 = self.apex.y;
-        let y1 = y0 - self.height;
-
         // Paramétrico para un rayo: O + tD
         // Ecuación implícita lateral (sin tapas):
         // (x^2 + z^2) - (r(y))^2 = 0
@@ -38,19 +37,21 @@ impl RayIntersect for ConeSection {
         // Expresión cuadrática en t
         // (ox + t dx)^2 + (oz + t dz)^2 = (r1 + k*(oy + t dy + h))^2
         // Expandir: A t^2 + B t + C = 0
-        let mut A = dx * dx + dz * dz - (k * dy) * (k * dy);
-        let mut B = 2.0 * (ox * dx + oz * dz - (k * dy) * (self.r1 + k * oy));
-        let mut C = ox * ox + oz * oz - (self.r1 + k * oy) * (self.r1 + k * oy);
+        // `base` es el radio a la altura del origen del rayo (y = -h es la base)
+        let base = self.r1 + k * (oy + self.height);
+        let a = dx * dx + dz * dz - (k * dy) * (k * dy);
+        let b = 2.0 * (ox * dx + oz * dz - (k * dy) * base);
+        let c = ox * ox + oz * oz - base * base;
 
-        let disc = B * B - 4.0 * A * C;
+        let disc = b * b - 4.0 * a * c;
         if disc < 0.0 {
             return Intersect::empty();
         }
 
         let sqrt_disc = disc.sqrt();
-        let mut t = (-B - sqrt_disc) / (2.0 * A);
+        let mut t = (-b - sqrt_disc) / (2.0 * a);
         if t < 0.0 {
-            t = (-B + sqrt_disc) / (2.0 * A);
+            t = (-b + sqrt_disc) / (2.0 * a);
             if t < 0.0 {
                 return Intersect::empty();
             }
@@ -63,19 +64,38 @@ impl RayIntersect for ConeSection {
 
         let point = ray_origin + ray_dir * t;
 
-        // Normal aproximada
-        let r_hit = self.r1 + k * (-y_hit);
-        let normal = Vec3::new(point.x - self.apex.x, r_hit * k, point.z - self.apex.z).normalize();
+        // Gradiente de (x^2 + z^2) - r(y)^2
+        let r_hit = self.r1 + k * (y_hit + self.height);
+        let normal =
+            Vec3::new(point.x - self.apex.x, -r_hit * k, point.z - self.apex.z).normalize();
+
+        // texturizado cilíndrico (u,v): u alrededor del eje, v baja desde el vértice
+        let u = (point.x.atan2(point.z) / std::f32::consts::PI + 1.0) * 0.5;
+        let v = (-y_hit / self.height).clamp(0.0, 1.0);
+        let tangent = Vec3::new(point.z - self.apex.z, 0.0, self.apex.x - point.x);
+        let bitangent = Vec3::new(0.0, -1.0, 0.0);
 
-        let mut hit = Intersect::new(point, normal, t, self.material);
+        let hit = Intersect::new(point, normal, t, self.material.clone()).with_surface(
+            Vec2::new(u, v),
+            tangent,
+            bitangent,
+        );
 
-        // texturizado cilíndrico (u,v)
-        if let Some(tex) = &self.texture {
-            let u = (point.x.atan2(point.z) / std::f32::consts::PI + 1.0) * 0.5;
-            let v = (-y_hit / self.height).clamp(0.0, 1.0);
-            hit.material.diffuse = tex.get_color(u, v);
+        match &self.texture {
+            Some(tex) => {
+                let scale = texture::footprint_scale(ray_dir, &normal, self.height);
+                hit.with_texture(tex, Vec2::new(u, v), scale)
+            }
+            None => hit,
         }
+    }
 
-        hit
+    fn bounding_box(&self) -> Aabb {
+        // r(y) es lineal, así que el radio máximo está en la base o en la tapa
+        let r = self.r1.abs().max(self.r2.abs());
+        Aabb::new(
+            Vec3::new(self.apex.x - r, self.apex.y - self.height, self.apex.z - r),
+            Vec3::new(self.apex.x + r, self.apex.y, self.apex.z + r),
+        )
     }
 }
@@ -0,0 +1,122 @@
+// controls.rs
+// Teclado y mouse de la ventana interactiva. Hay dos modos de cámara:
+//   órbita: flechas o arrastrar con el mouse giran alrededor de `center`,
+//           S/A o la rueda acercan y alejan
+//   vuelo:  WASD mueve, E/Q sube y baja, arrastrar el mouse o las flechas
+//           giran la vista desde el ojo
+// F cambia de modo.
+use entorno3d::camera::Camera;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+use std::f32::consts::PI;
+
+const ROTATION_SPEED: f32 = PI / 50.0;
+const ZOOM_SPEED: f32 = 0.3;
+const FLY_SPEED: f32 = 0.15;
+// Radianes por píxel de arrastre
+const MOUSE_SENSITIVITY: f32 = 0.005;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    Orbit,
+    Fly,
+}
+
+pub struct Controls {
+    mode: CameraMode,
+    // Posición del mouse en el cuadro anterior mientras se arrastra
+    last_mouse: Option<(f32, f32)>,
+}
+
+impl Controls {
+    pub fn new() -> Self {
+        Controls {
+            mode: CameraMode::Orbit,
+            last_mouse: None,
+        }
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    // Aplica la entrada de este cuadro a la cámara
+    pub fn update(&mut self, window: &Window, camera: &mut Camera) {
+        if window.is_key_pressed(Key::F, KeyRepeat::No) {
+            self.mode = match self.mode {
+                CameraMode::Orbit => CameraMode::Fly,
+                CameraMode::Fly => CameraMode::Orbit,
+            };
+        }
+
+        let (drag_x, drag_y) = self.mouse_drag(window);
+        match self.mode {
+            CameraMode::Orbit => {
+                if window.is_key_down(Key::Left) {
+                    camera.orbit(ROTATION_SPEED, 0.);
+                }
+                if window.is_key_down(Key::Right) {
+                    camera.orbit(-ROTATION_SPEED, 0.);
+                }
+                if window.is_key_down(Key::Up) {
+                    camera.orbit(0., -ROTATION_SPEED);
+                }
+                if window.is_key_down(Key::Down) {
+                    camera.orbit(0., ROTATION_SPEED);
+                }
+                // Arrastrar la escena hacia un lado la hace girar hacia ese lado
+                if drag_x != 0.0 || drag_y != 0.0 {
+                    camera.orbit(-drag_x * MOUSE_SENSITIVITY, -drag_y * MOUSE_SENSITIVITY);
+                }
+                if window.is_key_down(Key::S) {
+                    camera.zoom(ZOOM_SPEED); // acercar
+                }
+                if window.is_key_down(Key::A) {
+                    camera.zoom(-ZOOM_SPEED); // alejar
+                }
+                // Cada paso de la rueda cuenta igual sin importar la plataforma
+                if let Some((_, scroll)) = window.get_scroll_wheel()
+                    && scroll != 0.0
+                {
+                    camera.zoom(scroll.signum() * ZOOM_SPEED);
+                }
+            }
+            CameraMode::Fly => {
+                let axis = |positive: Key, negative: Key| {
+                    window.is_key_down(positive) as i32 - window.is_key_down(negative) as i32
+                };
+                let forward = axis(Key::W, Key::S) as f32 * FLY_SPEED;
+                let right = axis(Key::D, Key::A) as f32 * FLY_SPEED;
+                let up = axis(Key::E, Key::Q) as f32 * FLY_SPEED;
+                if forward != 0.0 || right != 0.0 || up != 0.0 {
+                    camera.fly(forward, right, up);
+                }
+
+                let yaw = axis(Key::Right, Key::Left) as f32 * ROTATION_SPEED
+                    + drag_x * MOUSE_SENSITIVITY;
+                let pitch =
+                    axis(Key::Up, Key::Down) as f32 * ROTATION_SPEED - drag_y * MOUSE_SENSITIVITY;
+                if yaw != 0.0 || pitch != 0.0 {
+                    camera.look(yaw, pitch);
+                }
+            }
+        }
+    }
+
+    // Píxeles que se movió el mouse desde el cuadro anterior con el botón
+    // izquierdo apretado
+    fn mouse_drag(&mut self, window: &Window) -> (f32, f32) {
+        if !window.get_mouse_down(MouseButton::Left) {
+            self.last_mouse = None;
+            return (0.0, 0.0);
+        }
+        let Some(position) = window.get_mouse_pos(MouseMode::Pass) else {
+            return (0.0, 0.0);
+        };
+        let drag = match self.last_mouse {
+            Some((x, y)) => (position.0 - x, position.1 - y),
+            None => (0.0, 0.0),
+        };
+        self.last_mouse = Some(position);
+        drag
+    }
+}
@@ -1,14 +1,16 @@
 //cube.rs
+use crate::aabb::Aabb;
 use crate::material::Material;
 use crate::ray_intersect::{Intersect, RayIntersect};
-use crate::texture::Texture;
-use nalgebra_glm::Vec3;
+use crate::texture::{self, Texture};
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
 
 pub struct Cube {
     pub min: Vec3,
     pub max: Vec3,
     pub material: Material,
-    pub textures: [Option<Texture>; 6], // [ -X, +X, -Y, +Y, -Z, +Z ]
+    pub textures: [Option<Arc<Texture>>; 6], // [ -X, +X, -Y, +Y, -Z, +Z ]
 }
 
 impl RayIntersect for Cube {
@@ -83,47 +85,70 @@ impl RayIntersect for Cube {
             face_index = 5;
         }
 
-        let mut hit = Intersect::new(point, normal, tmin, self.material);
-
         // Mapear coordenadas UV según la cara
+        let (u, v) = match face_index {
+            0 => (
+                // -X → usa (z,y)
+                (point.z - self.min.z) / (self.max.z - self.min.z),
+                (point.y - self.min.y) / (self.max.y - self.min.y),
+            ),
+            1 => (
+                // +X → usa (z,y)
+                (point.z - self.min.z) / (self.max.z - self.min.z),
+                (point.y - self.min.y) / (self.max.y - self.min.y),
+            ),
+            2 => (
+                // -Y → usa (x,z)
+                (point.x - self.min.x) / (self.max.x - self.min.x),
+                (point.z - self.min.z) / (self.max.z - self.min.z),
+            ),
+            3 => (
+                // +Y → usa (x,z)
+                (point.x - self.min.x) / (self.max.x - self.min.x),
+                (point.z - self.min.z) / (self.max.z - self.min.z),
+            ),
+            4 => (
+                // -Z → usa (x,y)
+                (point.x - self.min.x) / (self.max.x - self.min.x),
+                (point.y - self.min.y) / (self.max.y - self.min.y),
+            ),
+            5 => (
+                // +Z → usa (x,y)
+                (point.x - self.min.x) / (self.max.x - self.min.x),
+                (point.y - self.min.y) / (self.max.y - self.min.y),
+            ),
+            _ => (0.0, 0.0),
+        };
+
+        // Direcciones en las que crecen u y v en cada cara
+        let (tangent, bitangent) = match face_index {
+            0 | 1 => (Vec3::z(), Vec3::y()),
+            2 | 3 => (Vec3::x(), Vec3::z()),
+            _ => (Vec3::x(), Vec3::y()),
+        };
+
+        let mut hit = Intersect::new(point, normal, tmin, self.material.clone()).with_surface(
+            Vec2::new(u, v),
+            tangent,
+            bitangent,
+        );
+
         if let Some(tex) = &self.textures[face_index] {
-            let (u, v) = match face_index {
-                0 => (
-                    // -X → usa (z,y)
-                    (point.z - self.min.z) / (self.max.z - self.min.z),
-                    (point.y - self.min.y) / (self.max.y - self.min.y),
-                ),
-                1 => (
-                    // +X → usa (z,y)
-                    (point.z - self.min.z) / (self.max.z - self.min.z),
-                    (point.y - self.min.y) / (self.max.y - self.min.y),
-                ),
-                2 => (
-                    // -Y → usa (x,z)
-                    (point.x - self.min.x) / (self.max.x - self.min.x),
-                    (point.z - self.min.z) / (self.max.z - self.min.z),
-                ),
-                3 => (
-                    // +Y → usa (x,z)
-                    (point.x - self.min.x) / (self.max.x - self.min.x),
-                    (point.z - self.min.z) / (self.max.z - self.min.z),
-                ),
-                4 => (
-                    // -Z → usa (x,y)
-                    (point.x - self.min.x) / (self.max.x - self.min.x),
-                    (point.y - self.min.y) / (self.max.y - self.min.y),
-                ),
-                5 => (
-                    // +Z → usa (x,y)
-                    (point.x - self.min.x) / (self.max.x - self.min.x),
-                    (point.y - self.min.y) / (self.max.y - self.min.y),
-                ),
-                _ => (0.0, 0.0),
+            // Tamaño de la cara en el mundo, para elegir el nivel de mipmap
+            let size = self.max - self.min;
+            let face_size = match face_index {
+                0 | 1 => size.y.max(size.z),
+                2 | 3 => size.x.max(size.z),
+                _ => size.x.max(size.y),
             };
-
-            hit.material.diffuse = tex.get_color(u, v);
+            let scale = texture::footprint_scale(ray_dir, &normal, face_size);
+            hit = hit.with_texture(tex, Vec2::new(u, v), scale);
         }
 
         hit
     }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new(self.min, self.max)
+    }
 }
@@ -0,0 +1,77 @@
+// environment.rs
+// Lo que ven los rayos que no golpean ningún objeto: un color plano,
+// una imagen equirectangular (panorama 360°) o un cube map de seis caras.
+use crate::color::{Color, LinearColor};
+use crate::texture::Texture;
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+pub enum Environment {
+    Color(LinearColor),
+    Equirectangular {
+        texture: Texture,
+        intensity: f32,
+    },
+    /// Caras en el mismo orden que Cube::textures: [ -X, +X, -Y, +Y, -Z, +Z ],
+    /// cada una vista desde el centro del cubo
+    CubeMap {
+        faces: Box<[Texture; 6]>,
+        intensity: f32,
+    },
+}
+
+impl Default for Environment {
+    // El azul oscuro de siempre
+    fn default() -> Self {
+        Environment::Color(LinearColor::from(Color::new(4, 12, 36)))
+    }
+}
+
+impl Environment {
+    /// Radiancia que llega desde `direction` (no hace falta que sea unitaria)
+    pub fn sample(&self, direction: &Vec3) -> LinearColor {
+        match self {
+            Environment::Color(color) => *color,
+            Environment::Equirectangular { texture, intensity } => {
+                let d = direction.normalize();
+                // -Z (hacia donde mira la cámara por defecto) queda en el centro de la imagen
+                let u = 0.5 + d.x.atan2(-d.z) / (2.0 * PI);
+                let v = 0.5 + d.y.clamp(-1.0, 1.0).asin() / PI;
+                texture.get_color(u, v) * *intensity
+            }
+            Environment::CubeMap { faces, intensity } => {
+                let (face, u, v) = cube_face(direction);
+                faces[face].get_color(u, v) * *intensity
+            }
+        }
+    }
+}
+
+// Cara del cube map y coordenadas UV (v hacia arriba) para una dirección
+fn cube_face(d: &Vec3) -> (usize, f32, f32) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    // Cada cara se ve desde el centro con la derecha en forward × arriba;
+    // las caras de arriba y abajo continúan la vista hacia -Z
+    let (face, sc, tc, major) = if ax >= ay && ax >= az {
+        if d.x > 0.0 {
+            (1, d.z, d.y, ax)
+        } else {
+            (0, -d.z, d.y, ax)
+        }
+    } else if ay >= az {
+        if d.y > 0.0 {
+            (3, d.x, d.z, ay)
+        } else {
+            (2, d.x, -d.z, ay)
+        }
+    } else if d.z > 0.0 {
+        (5, -d.x, d.y, az)
+    } else {
+        (4, d.x, d.y, az)
+    };
+
+    if major == 0.0 {
+        return (face, 0.5, 0.5);
+    }
+    (face, 0.5 * (sc / major + 1.0), 0.5 * (tc / major + 1.0))
+}
@@ -0,0 +1,107 @@
+// filter.rs
+// Antialiasing: posiciones de las muestras dentro del píxel y filtro de
+// reconstrucción con el que se promedian.
+use crate::color::LinearColor;
+use rand::Rng;
+use rand::rngs::SmallRng;
+use std::ops::AddAssign;
+
+/// Filtro de reconstrucción. Las muestras se reparten en el soporte del filtro
+/// (que puede pasarse del píxel) y se promedian con su peso.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelFilter {
+    /// Promedio simple dentro del píxel
+    #[default]
+    Box,
+    /// Peso lineal que cae a 0 a un píxel del centro
+    Tent,
+    /// Gaussiana truncada a 1.5 píxeles: el más suave
+    Gaussian,
+}
+
+const GAUSSIAN_ALPHA: f32 = 2.0;
+
+impl PixelFilter {
+    /// Distancia máxima (en píxeles) desde el centro a la que llega una muestra
+    pub fn radius(self) -> f32 {
+        match self {
+            PixelFilter::Box => 0.5,
+            PixelFilter::Tent => 1.0,
+            PixelFilter::Gaussian => 1.5,
+        }
+    }
+
+    /// Peso de una muestra a (dx, dy) píxeles del centro
+    pub fn weight(self, dx: f32, dy: f32) -> f32 {
+        let radius = self.radius();
+        let axis = |d: f32| match self {
+            PixelFilter::Box => 1.0,
+            PixelFilter::Tent => (1.0 - d.abs() / radius).max(0.0),
+            PixelFilter::Gaussian => {
+                let edge = (-GAUSSIAN_ALPHA * radius * radius).exp();
+                ((-GAUSSIAN_ALPHA * d * d).exp() - edge).max(0.0)
+            }
+        };
+        axis(dx) * axis(dy)
+    }
+
+    /// Desplazamiento de la muestra `index` de `count`: el soporte se divide en
+    /// una cuadrícula y cada muestra cae al azar dentro de su celda (estratificado).
+    /// La cuadrícula tiene exactamente `count` celdas, lo más cuadrada posible
+    /// (con un primo queda en franjas): si sobrara una celda vacía, el promedio
+    /// se correría hacia el resto del píxel.
+    pub fn sample_offset(self, index: u32, count: u32, rng: &mut SmallRng) -> (f32, f32) {
+        let count = count.max(1);
+        let rows = (1..=count.isqrt())
+            .rev()
+            .find(|rows| count.is_multiple_of(*rows))
+            .unwrap_or(1);
+        let columns = count / rows;
+        let column = index % columns;
+        let row = index / columns;
+
+        let u = (column as f32 + rng.random::<f32>()) / columns as f32;
+        let v = (row as f32 + rng.random::<f32>()) / rows as f32;
+        let diameter = 2.0 * self.radius();
+        ((u - 0.5) * diameter, (v - 0.5) * diameter)
+    }
+}
+
+/// Muestras de un píxel ya ponderadas por el filtro: Σ w·color y Σ w.
+/// Se dividen recién al resolver, así los pesos no se cancelan cuando cada
+/// pasada del render progresivo aporta una sola muestra.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilteredSample {
+    pub color: LinearColor,
+    pub weight: f32,
+}
+
+impl FilteredSample {
+    pub fn empty() -> Self {
+        FilteredSample {
+            color: LinearColor::black(),
+            weight: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, color: LinearColor, weight: f32) {
+        self.color += color * weight;
+        self.weight += weight;
+    }
+
+    /// Promedio ponderado (negro si no hay peso)
+    pub fn resolve(&self) -> LinearColor {
+        if self.weight > 0.0 {
+            self.color * (1.0 / self.weight)
+        } else {
+            LinearColor::black()
+        }
+    }
+}
+
+impl AddAssign for FilteredSample {
+    fn add_assign(&mut self, other: FilteredSample) {
+        self.color += other.color;
+        self.weight += other.weight;
+    }
+}
@@ -1,4 +1,6 @@
 // framebuffer.rs
+use image::{ImageResult, Rgb, RgbImage};
+use std::path::Path;
 
 pub struct Framebuffer {
     pub width: usize,
@@ -38,4 +40,13 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
+
+    // Guarda el contenido del buffer como PNG (o el formato que indique la extensión)
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        let image = RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let pixel = self.buffer[y as usize * self.width + x as usize];
+            Rgb([(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+        });
+        image.save(path)
+    }
 }
@@ -0,0 +1,48 @@
+// lib.rs
+// Trazador de rayos: primitivas, materiales, cámara y funciones de render.
+// El visor interactivo (main.rs) es solo un binario delgado sobre esta biblioteca.
+pub mod aabb;
+pub mod accumulator;
+pub mod animation;
+pub mod brdf;
+pub mod bvh;
+pub mod camera;
+pub mod color;
+pub mod cone;
+pub mod cube;
+pub mod environment;
+pub mod filter;
+pub mod framebuffer;
+pub mod light;
+pub mod material;
+pub mod mesh;
+pub mod obj;
+pub mod pathtracer;
+pub mod pawn;
+pub mod plane;
+pub mod postprocess;
+pub mod ray_intersect;
+pub mod raytracer;
+pub mod render_target;
+pub mod scene;
+pub mod sphere;
+pub mod texture;
+pub mod transform;
+
+pub use accumulator::Accumulator;
+pub use animation::{CameraAnimation, CameraKeyframe, Interpolation};
+pub use bvh::Bvh;
+pub use camera::{Camera, Projection};
+pub use color::{Color, LinearColor};
+pub use environment::Environment;
+pub use filter::{FilteredSample, PixelFilter};
+pub use framebuffer::Framebuffer;
+pub use light::{Light, LightShape};
+pub use material::{Material, PbrParams, ShadingModel};
+pub use mesh::Mesh;
+pub use postprocess::{PostProcess, ToneMapping};
+pub use ray_intersect::{Intersect, RayIntersect};
+pub use raytracer::{Integrator, RenderSettings, accumulate, cast_ray, render, render_geometry};
+pub use render_target::RenderTarget;
+pub use scene::Scene;
+pub use transform::Transform;
@@ -1,11 +1,37 @@
 //light.rs
 use crate::color::Color;
 use nalgebra_glm::Vec3;
+use rand::Rng;
+use rand::rngs::SmallRng;
+use std::f32::consts::PI;
 
 pub struct Light {
-    pub position: Vec3,
+    pub position: Vec3, // centro de la luz
     pub color: Color,
     pub intensity: f32,
+    pub shape: LightShape,
+    // Rayos de sombra por punto sombreado (1 para luces puntuales)
+    pub samples: u32,
+}
+
+/// Forma de la luz. Una luz de área se comporta como muchas luces puntuales
+/// repartidas sobre su superficie que suman `intensity`; lo que cambia es que
+/// cada punto sombreado ve una fracción de ellas y aparece la penumbra.
+#[derive(Debug, Clone, Copy)]
+pub enum LightShape {
+    Point,
+    /// Rectángulo centrado en `position` con lados `u` y `v`
+    Rectangle {
+        u: Vec3,
+        v: Vec3,
+    },
+    Disk {
+        normal: Vec3,
+        radius: f32,
+    },
+    Sphere {
+        radius: f32,
+    },
 }
 
 impl Light {
@@ -14,6 +40,51 @@ impl Light {
             position,
             color,
             intensity,
+            shape: LightShape::Point,
+            samples: 1,
+        }
+    }
+
+    pub fn with_shape(mut self, shape: LightShape, samples: u32) -> Self {
+        self.shape = shape;
+        self.samples = match shape {
+            LightShape::Point => 1, // todos los rayos irían al mismo punto
+            _ => samples.max(1),
+        };
+        self
+    }
+
+    /// Punto al azar sobre la luz, visto desde `from`
+    pub fn sample_position(&self, from: &Vec3, rng: &mut SmallRng) -> Vec3 {
+        match self.shape {
+            LightShape::Point => self.position,
+            LightShape::Rectangle { u, v } => {
+                self.position + u * (rng.random::<f32>() - 0.5) + v * (rng.random::<f32>() - 0.5)
+            }
+            LightShape::Disk { normal, radius } => self.position + disk_point(&normal, radius, rng),
+            LightShape::Sphere { radius } => {
+                // Desde `from` la esfera se ve como un disco perpendicular a la dirección
+                let axis = from - self.position;
+                if axis.magnitude_squared() <= radius * radius {
+                    return self.position;
+                }
+                self.position + disk_point(&axis.normalize(), radius, rng)
+            }
         }
     }
 }
+
+// Punto uniforme en un disco de radio `radius` centrado en el origen
+fn disk_point(normal: &Vec3, radius: f32, rng: &mut SmallRng) -> Vec3 {
+    let helper = if normal.x.abs() > 0.9 {
+        Vec3::y()
+    } else {
+        Vec3::x()
+    };
+    let tangent = normal.cross(&helper).normalize();
+    let bitangent = normal.cross(&tangent).normalize();
+
+    let r = radius * rng.random::<f32>().sqrt();
+    let phi = 2.0 * PI * rng.random::<f32>();
+    tangent * (r * phi.cos()) + bitangent * (r * phi.sin())
+}
@@ -1,273 +1,249 @@
 // main.rs
-use crate::pawn::Pawn;
+mod controls;
+
+use controls::{CameraMode, Controls};
+use entorno3d::accumulator::Accumulator;
+use entorno3d::filter::PixelFilter;
+use entorno3d::framebuffer::Framebuffer;
+use entorno3d::postprocess::ToneMapping;
+use entorno3d::raytracer::{Integrator, RenderSettings, render};
+use entorno3d::render_target::RenderTarget;
+use entorno3d::scene::Scene;
 use minifb::{Key, Window, WindowOptions};
-use nalgebra_glm::{Vec3, normalize};
-use std::f32::consts::PI;
+use std::path::Path;
 use std::time::Duration;
-mod camera;
-mod color;
 
-mod cube;
-mod framebuffer;
-mod pawn;
-
-mod light;
-mod material;
-mod plane;
-mod ray_intersect;
-mod sphere;
-mod texture; // si no lo tienes aún
-
-use camera::Camera;
-use color::Color;
-use cube::Cube;
-use framebuffer::Framebuffer;
-use light::Light;
-use material::Material;
-
-use ray_intersect::{Intersect, RayIntersect};
-use sphere::Sphere;
-use texture::Texture;
-
-const SHADOW_BIAS: f32 = 1e-4;
-
-fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
-    incident - 2.0 * incident.dot(normal) * normal
-}
-
-fn refract(incident: &Vec3, normal: &Vec3, eta: f32) -> Option<Vec3> {
-    let n_dot_i = normal.dot(incident);
-    let k = 1.0 - eta * eta * (1.0 - n_dot_i * n_dot_i);
-
-    if k < 0.0 {
-        None // Reflexión interna total
-    } else {
-        Some(eta * incident - (eta * n_dot_i + k.sqrt()) * normal)
-    }
+// Opciones de línea de comandos:
+//   entorno3D [escena.toml] [--output salida.png] [--width N] [--height N]
+//             [--integrator whitted|path] [--spp N] [--filter box|tent|gaussian]
+//             [--frames N] [--exposure EV] [--tonemap clamp|reinhard|aces] [--no-srgb]
+//             [--aux]
+// Con --output se renderiza un solo cuadro a archivo sin abrir ventana.
+// Con --frames además se recorre la [animation] de la escena y se guardan N
+// cuadros numerados: salida.png -> salida_0000.png, salida_0001.png, ...
+// Con --aux también se guardan la profundidad, las normales y los IDs de
+// objeto junto a la imagen: salida_depth.png, salida_normal.png, salida_id.png
+struct Options {
+    scene_path: String,
+    output: Option<String>,
+    frames: Option<usize>,
+    aux: bool,
+    width: usize,
+    height: usize,
+    settings: RenderSettings,
 }
 
-fn cast_shadow(intersect: &Intersect, light: &Light, objects: &[Box<dyn RayIntersect>]) -> f32 {
-    let light_dir = (light.position - intersect.point).normalize();
-    let light_distance = (light.position - intersect.point).magnitude();
-
-    let offset_normal = intersect.normal * SHADOW_BIAS;
-    let shadow_ray_origin = if light_dir.dot(&intersect.normal) < 0.0 {
-        intersect.point - offset_normal
-    } else {
-        intersect.point + offset_normal
+// Muestras por píxel tras las cuales el render progresivo se detiene
+const MAX_PROGRESSIVE_SAMPLES: u32 = 256;
+
+const USAGE: &str =
+    "uso: entorno3D [escena.toml] [--output salida.png] [--width ancho] [--height alto]
+                 [--integrator whitted|path] [--spp muestras] [--filter box|tent|gaussian]
+                 [--frames cuadros] [--exposure EV] [--tonemap clamp|reinhard|aces] [--no-srgb]
+                 [--aux]";
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        scene_path: "scenes/default.toml".to_string(),
+        output: None,
+        frames: None,
+        aux: false,
+        width: 800,
+        height: 600,
+        settings: RenderSettings::default(),
     };
 
-    let mut shadow_intensity = 0.0;
-
-    for object in objects {
-        let shadow_intersect = object.ray_intersect(&shadow_ray_origin, &light_dir);
-        if shadow_intersect.is_intersecting && shadow_intersect.distance < light_distance {
-            let distance_ratio = shadow_intersect.distance / light_distance;
-            shadow_intensity = 1.0 - distance_ratio.powf(2.0).min(1.0);
-            break;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                options.output = Some(args.next().ok_or("falta la ruta para --output")?);
+            }
+            "--width" => options.width = parse_size(args.next(), "--width")?,
+            "--height" => options.height = parse_size(args.next(), "--height")?,
+            "--integrator" => {
+                options.settings.integrator = match args.next().as_deref() {
+                    Some("whitted") => Integrator::Whitted,
+                    Some("path") => Integrator::PathTracer,
+                    Some(other) => return Err(format!("integrador desconocido: {}", other)),
+                    None => return Err("falta el valor para --integrator".to_string()),
+                };
+            }
+            "--filter" => {
+                options.settings.filter = match args.next().as_deref() {
+                    Some("box") => PixelFilter::Box,
+                    Some("tent") => PixelFilter::Tent,
+                    Some("gaussian") => PixelFilter::Gaussian,
+                    Some(other) => return Err(format!("filtro desconocido: {}", other)),
+                    None => return Err("falta el valor para --filter".to_string()),
+                };
+            }
+            "--exposure" => {
+                let value = args.next().ok_or("falta el valor para --exposure")?;
+                options.settings.post.exposure = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|ev| ev.is_finite())
+                    .ok_or(format!("valor inválido para --exposure: {}", value))?;
+            }
+            "--tonemap" => {
+                options.settings.post.tone_mapping = match args.next().as_deref() {
+                    Some("clamp") => ToneMapping::Clamp,
+                    Some("reinhard") => ToneMapping::Reinhard,
+                    Some("aces") => ToneMapping::Aces,
+                    Some(other) => return Err(format!("tone mapping desconocido: {}", other)),
+                    None => return Err("falta el valor para --tonemap".to_string()),
+                };
+            }
+            "--no-srgb" => options.settings.post.srgb = false,
+            "--aux" => options.aux = true,
+            "--frames" => options.frames = Some(parse_size(args.next(), "--frames")?),
+            "--spp" => {
+                options.settings.samples_per_pixel = parse_size(args.next(), "--spp")? as u32;
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with('-') => {
+                return Err(format!("opción desconocida: {}\n{}", flag, USAGE));
+            }
+            path => options.scene_path = path.to_string(),
         }
     }
 
-    shadow_intensity
+    if options.frames.is_some() && options.output.is_none() {
+        return Err("--frames necesita --output para nombrar los cuadros".to_string());
+    }
+    if options.aux && (options.output.is_none() || options.frames.is_some()) {
+        return Err("--aux necesita --output y no se combina con --frames".to_string());
+    }
+    Ok(options)
 }
 
-pub fn cast_ray(
-    ray_origin: &Vec3,
-    ray_direction: &Vec3,
-    objects: &[Box<dyn RayIntersect>],
-    light: &Light,
-    depth: u32,
-) -> Color {
-    const MAX_DEPTH: u32 = 5;
-
-    if depth > MAX_DEPTH {
-        return Color::new(4, 12, 36);
+fn parse_size(value: Option<String>, flag: &str) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("falta el valor para {}", flag))?;
+    match value.parse::<usize>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("valor inválido para {}: {}", flag, value)),
     }
+}
 
-    let mut intersect = Intersect::empty();
-    let mut zbuffer = f32::INFINITY;
-
-    for object in objects {
-        let i = object.ray_intersect(ray_origin, ray_direction);
-        if i.is_intersecting && i.distance < zbuffer {
-            zbuffer = i.distance;
-            intersect = i;
-        }
+fn render_sequence(
+    scene: &mut Scene,
+    frames: usize,
+    output: &str,
+    options: &Options,
+) -> Result<(), String> {
+    let animation = scene
+        .animation
+        .take()
+        .ok_or("la escena no tiene [animation] para --frames")?;
+
+    let output = Path::new(output);
+    let stem = output
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("frame");
+    let extension = output.extension().and_then(|s| s.to_str()).unwrap_or("png");
+
+    let mut framebuffer = Framebuffer::new(options.width, options.height);
+    for frame in 0..frames {
+        animation.apply(animation.frame_time(frame, frames), &mut scene.camera);
+        render(&mut framebuffer, scene, &options.settings);
+
+        let path = output.with_file_name(format!("{}_{:04}.{}", stem, frame, extension));
+        framebuffer
+            .save(&path)
+            .map_err(|e| format!("No se pudo guardar {}: {}", path.display(), e))?;
+        println!("cuadro {}/{}: {}", frame + 1, frames, path.display());
     }
+    Ok(())
+}
 
-    if !intersect.is_intersecting {
-        return Color::new(4, 12, 36);
+// Renderiza a través de un RenderTarget y guarda sus canales de geometría
+// al lado de `output`
+fn render_with_aux(
+    scene: &Scene,
+    framebuffer: &mut Framebuffer,
+    output: &str,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    let mut target = RenderTarget::new(framebuffer.width, framebuffer.height).with_geometry();
+    target.add_pass(scene, settings);
+    target.resolve(framebuffer, &settings.post);
+
+    let output = Path::new(output);
+    let stem = output
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("render");
+    let channel_path = |channel: &str| output.with_file_name(format!("{}_{}.png", stem, channel));
+    let saved = [
+        ("depth", target.save_depth(channel_path("depth"))),
+        ("normal", target.save_normals(channel_path("normal"))),
+        ("id", target.save_object_ids(channel_path("id"))),
+    ];
+    for (channel, result) in saved {
+        result.map_err(|e| {
+            format!(
+                "No se pudo guardar {}: {}",
+                channel_path(channel).display(),
+                e
+            )
+        })?;
     }
+    Ok(())
+}
 
-    let light_dir = (light.position - intersect.point).normalize();
-    let view_dir = (ray_origin - intersect.point).normalize();
-    let reflect_dir = reflect(&-light_dir, &intersect.normal);
-
-    let shadow_intensity = cast_shadow(&intersect, light, objects);
-    let light_intensity = light.intensity * (1.0 - shadow_intensity);
-
-    let diffuse_intensity = intersect.normal.dot(&light_dir).max(0.0).min(1.0);
-    let diffuse = intersect.material.diffuse
-        * intersect.material.albedo[0]
-        * diffuse_intensity
-        * light_intensity;
-
-    let specular_intensity = view_dir
-        .dot(&reflect_dir)
-        .max(0.0)
-        .powf(intersect.material.specular);
-    let specular =
-        light.color * intersect.material.albedo[1] * specular_intensity * light_intensity;
-
-    let mut reflection_color = Color::new(0, 0, 0);
-    if intersect.material.albedo[2] > 0.0 {
-        let reflected_dir = reflect(ray_direction, &intersect.normal);
-        let reflection_origin = intersect.point + intersect.normal * SHADOW_BIAS;
-        reflection_color = cast_ray(
-            &reflection_origin,
-            &reflected_dir,
-            objects,
-            light,
-            depth + 1,
-        ) * intersect.material.albedo[2];
+fn window_title(mode: CameraMode) -> &'static str {
+    match mode {
+        CameraMode::Orbit => "Raytracer - órbita (F: vuelo)",
+        CameraMode::Fly => "Raytracer - vuelo (F: órbita)",
     }
+}
 
-    let mut refraction_color = Color::new(0, 0, 0);
-    if intersect.material.albedo[3] > 0.0 {
-        let eta = if ray_direction.dot(&intersect.normal) < 0.0 {
-            1.0 / intersect.material.albedo[3]
-        } else {
-            intersect.material.albedo[3]
-        };
-
-        if let Some(refracted_dir) = refract(ray_direction, &intersect.normal, eta) {
-            let refraction_origin = intersect.point - intersect.normal * SHADOW_BIAS;
-            refraction_color = cast_ray(
-                &refraction_origin,
-                &refracted_dir,
-                objects,
-                light,
-                depth + 1,
-            ) * intersect.material.albedo[3];
+fn main() {
+    let options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+
+    // La escena se describe en un archivo TOML; por defecto usamos scenes/default.toml
+    let mut scene = Scene::load(&options.scene_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let settings = options.settings;
+
+    // Secuencia animada: un render por cuadro, sin ventana
+    if let (Some(frames), Some(output)) = (options.frames, &options.output) {
+        if let Err(e) = render_sequence(&mut scene, frames, output, &options) {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
+        return;
     }
 
-    let mut reflected_light_color = Color::new(0, 0, 0);
-    if intersect.material.albedo[2] > 0.0 {
-        let reflected_dir = reflect(ray_direction, &intersect.normal);
-        let reflection_origin = intersect.point + intersect.normal * SHADOW_BIAS;
-
-        for _ in 0..3 {
-            let jitter = Vec3::new(
-                rand::random::<f32>() - 0.5,
-                rand::random::<f32>() - 0.5,
-                rand::random::<f32>() - 0.5,
-            ) * 0.1;
-
-            let jittered_dir = (reflected_dir + jitter).normalize();
-
-            reflected_light_color = reflected_light_color
-                + cast_ray(&reflection_origin, &jittered_dir, objects, light, depth + 1);
+    // Modo sin ventana: un solo render directo a archivo
+    if let Some(output) = &options.output {
+        let mut framebuffer = Framebuffer::new(options.width, options.height);
+        if options.aux {
+            if let Err(e) = render_with_aux(&scene, &mut framebuffer, output, &settings) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        } else {
+            render(&mut framebuffer, &scene, &settings);
         }
-
-        reflected_light_color = reflected_light_color * (1.0 / 3.0) * intersect.material.albedo[2];
-    }
-    // === 🔥 Luz ambiental ===
-    let ambient_strength = 0.22; // controla qué tan fuerte es la luz ambiente
-    let ambient = intersect.material.diffuse * ambient_strength;
-
-    diffuse + specular + reflection_color + refraction_color + reflected_light_color + ambient
-}
-
-pub fn render(
-    framebuffer: &mut Framebuffer,
-    objects: &[Box<dyn RayIntersect>],
-    camera: &Camera,
-    light: &Light,
-) {
-    let width = framebuffer.width as f32;
-    let height = framebuffer.height as f32;
-    let aspect_ratio = width / height;
-    let fov = PI / 3.0;
-    let perspective_scale = (fov * 0.33).tan();
-
-    for y in 0..framebuffer.height {
-        for x in 0..framebuffer.width {
-            let screen_x = (2.0 * x as f32) / width - 1.0;
-            let screen_y = -(2.0 * y as f32) / height + 1.0;
-
-            let screen_x = screen_x * aspect_ratio * perspective_scale;
-            let screen_y = screen_y * perspective_scale;
-
-            let ray_direction = normalize(&Vec3::new(screen_x, screen_y, -1.0));
-
-            let rotated_direction = camera.basis_change(&ray_direction);
-
-            let pixel_color = cast_ray(&camera.eye, &rotated_direction, objects, light, 0);
-
-            framebuffer.set_current_color(pixel_color.to_hex());
-            framebuffer.point(x, y);
+        if let Err(e) = framebuffer.save(output) {
+            eprintln!("No se pudo guardar {}: {}", output, e);
+            std::process::exit(1);
         }
+        return;
     }
-}
-
-fn main() {
-    // Usar la misma textura PNG para los 6 lados
-    //agrega una botalla de klein transparente de vidrio
 
-    let floor_cube = Cube {
-        min: Vec3::new(-3.0, -1.0, -3.0), // más ancho y delgado
-        max: Vec3::new(3.0, -0.8, 3.0),   // altura pequeña
-        material: Material::new(Color::new(200, 200, 200), 50.0, [1.0, 0.5, 0.15, 0.2]),
-        textures: [
-            Some(Texture::from_file("assets/marmol_lado2.png")),
-            Some(Texture::from_file("assets/marmol_lado.png")), // se ve mal
-            Some(Texture::from_file("assets/Base_marmol.png")), // abajo
-            Some(Texture::from_file("assets/marmol.png")),      //arriba
-            Some(Texture::from_file("assets/marmol_lado2.png")),
-            Some(Texture::from_file("assets/marmol_lado.png")), // se ve mal
-        ],
-    };
-
-    let tex_negx = Texture::from_file("assets/cuboR2.png");
-    let tex_posx = Texture::from_file("assets/cuboL2.png");
-    let tex_negy = Texture::from_file("assets/cuboB2.png");
-    let tex_posy = Texture::from_file("assets/cuboF2.png");
-    let tex_negz = Texture::from_file("assets/cuboD2.png");
-    let tex_posz = Texture::from_file("assets/cuboU2.png");
-
-    // Cubo decorativo en una esquina del piso
-    let deco_cube = Cube {
-        min: Vec3::new(-1.8, -0.8, -1.8),
-        max: Vec3::new(-1.2, -0.2, -1.2),
-        material: Material::new(Color::new(50, 150, 200), 80.0, [0.6, 0.3, 0.04, 0.1]),
-        textures: [
-            Some(tex_negx),
-            Some(tex_posx),
-            Some(tex_negy),
-            Some(tex_posy),
-            Some(tex_negz),
-            Some(tex_posz),
-        ],
-    };
-
-    let pawn = Pawn {
-        base: Vec3::new(-1.1, -0.8, 0.5), // centro del tablero sobre el piso
-        scale: 0.5,                       // menor que 1/8 del ancho (6/8 = 0.75, usamos 0.5)
-        material: Material::new(Color::new(180, 140, 90), 80.0, [0.8, 0.3, 0.04, 0.0]),
-    };
-
-    // Esfera en la esquina opuesta del piso
-    let sphere = Sphere {
-        center: Vec3::new(2.5, -0.4, 2.5),
-        radius: 0.5,
-        material: Material::new(Color::new(200, 50, 50), 100.0, [0.7, 0.3, 0.2, 0.1]), //new(Color::new(50, 50, 50), 100.0, [0.7, 0.3, 0.6, 0.1]),
-    };
-
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
+    let window_width = options.width;
+    let window_height = options.height;
+    let framebuffer_width = options.width;
+    let framebuffer_height = options.height;
     let frame_delay = Duration::from_millis(16);
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
@@ -282,50 +258,35 @@ fn main() {
     window.set_position(500, 500);
     window.update();
 
-    // Objetos en la escena
-    let objects: Vec<Box<dyn RayIntersect>> = vec![
-        Box::new(floor_cube),
-        Box::new(deco_cube),
-        Box::new(sphere),
-        Box::new(pawn),
-    ];
+    let mut controls = Controls::new();
+    let mut mode = controls.mode();
+    window.set_title(window_title(mode));
 
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 2.0, 7.0),
-        Vec3::new(0.0, -0.5, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-    );
-    let rotation_speed = PI / 50.0;
-    let zoom_speed = 0.3;
-    let light = Light::new(Vec3::new(2.0, 3.0, 5.0), Color::new(255, 223, 250), 2.0);
+    // Con la cámara quieta se siguen promediando muestras (render progresivo)
+    let mut accumulator = Accumulator::new(framebuffer_width, framebuffer_height);
 
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
             break;
         }
-        // rotacion
-        if window.is_key_down(Key::Left) {
-            camera.orbit(rotation_speed, 0.);
-        }
-        if window.is_key_down(Key::Right) {
-            camera.orbit(-rotation_speed, 0.);
-        }
-        if window.is_key_down(Key::Up) {
-            camera.orbit(0., -rotation_speed);
-        }
-        if window.is_key_down(Key::Down) {
-            camera.orbit(0., rotation_speed);
+        let previous_view = (scene.camera.eye, scene.camera.center, scene.camera.up);
+
+        controls.update(&window, &mut scene.camera);
+        if controls.mode() != mode {
+            mode = controls.mode();
+            window.set_title(window_title(mode));
         }
-        // 🔎 Zoom
-        if window.is_key_down(Key::S) {
-            camera.zoom(zoom_speed); // acercar
+
+        // Si la cámara se movió, lo acumulado ya no sirve
+        if (scene.camera.eye, scene.camera.center, scene.camera.up) != previous_view {
+            accumulator.reset();
         }
-        if window.is_key_down(Key::A) {
-            camera.zoom(-zoom_speed); // alejar
+        let samples = accumulator.passes() * settings.samples_per_pixel.max(1);
+        if samples < MAX_PROGRESSIVE_SAMPLES {
+            accumulator.add_pass(&scene, &settings);
+            accumulator.resolve(&mut framebuffer, &settings.post);
         }
 
-        render(&mut framebuffer, &objects, &camera, &light);
-
         window
             .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
             .unwrap();
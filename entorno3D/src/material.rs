@@ -1,27 +1,144 @@
 //material.rs
-use crate::color::Color;
+use crate::color::{Color, LinearColor};
+use crate::texture::Texture;
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Material {
-    pub diffuse: Color,
+    // Color difuso (o base, con Pbr) en lineal
+    pub diffuse: LinearColor,
     pub specular: f32,
-    pub albedo: [f32; 4], // 0: difuso, 1: especular, 2: reflexión, 3: refracción
+    pub albedo: [f32; 3], // 0: difuso, 1: especular, 2: reflexión
+    // Fracción de la luz que atraviesa la superficie (0: opaco, 1: vidrio)
+    pub transparency: f32,
+    // Índice de refracción: desvía la luz transmitida y fija el reflejo de Fresnel
+    pub ior: f32,
+    pub surface_map: Option<SurfaceMap>,
+    pub model: ShadingModel,
+}
+
+/// Cómo se sombrea el material. Con `Phong` se usan `specular` y `albedo`;
+/// con `Pbr`, `diffuse` es el color base y `albedo`/`specular` se ignoran.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ShadingModel {
+    #[default]
+    Phong,
+    Pbr(PbrParams),
+}
+
+/// Parámetros del modelo metálico-rugoso (Cook-Torrance/GGX)
+#[derive(Debug, Clone, Copy)]
+pub struct PbrParams {
+    pub metallic: f32,  // 0: dieléctrico, 1: metal
+    pub roughness: f32, // 0: espejo, 1: completamente mate
+    pub emission: LinearColor,
+}
+
+impl Default for PbrParams {
+    fn default() -> Self {
+        PbrParams {
+            metallic: 0.0,
+            roughness: 0.5,
+            emission: LinearColor::black(),
+        }
+    }
+}
+
+/// Mapa que modifica la normal de la superficie al sombrear.
+/// La textura se comparte (Arc) porque el material se copia en cada impacto;
+/// guarda datos, no colores, así que se carga con `Texture::load_raw`.
+#[derive(Debug, Clone)]
+pub enum SurfaceMap {
+    /// Normal en espacio tangente codificada en RGB (convención OpenGL: +V en verde)
+    Normal(Arc<Texture>),
+    /// Mapa de alturas en escala de grises; `strength` multiplica la pendiente
+    Bump {
+        texture: Arc<Texture>,
+        strength: f32,
+    },
 }
 
 impl Material {
-    pub fn new(diffuse: Color, specular: f32, albedo: [f32; 4]) -> Self {
+    /// `diffuse` en sRGB de 8 bits, como en las escenas
+    pub fn new(diffuse: Color, specular: f32, albedo: [f32; 3]) -> Self {
         Material {
-            diffuse,
+            diffuse: LinearColor::from(diffuse),
             specular,
             albedo,
+            transparency: 0.0,
+            ior: 1.5, // vidrio común
+            surface_map: None,
+            model: ShadingModel::Phong,
         }
     }
 
-    pub fn black() -> Self {
+    pub fn pbr(base_color: Color, params: PbrParams) -> Self {
         Material {
-            diffuse: Color::new(0, 0, 0),
-            specular: 0.0,
-            albedo: [0.0, 0.0, 0.0, 0.0],
+            model: ShadingModel::Pbr(params),
+            ..Material::new(base_color, 0.0, [0.0, 0.0, 0.0])
+        }
+    }
+
+    pub fn black() -> Self {
+        Material::new(Color::new(0, 0, 0), 0.0, [0.0, 0.0, 0.0])
+    }
+
+    pub fn with_transparency(mut self, transparency: f32, ior: f32) -> Self {
+        self.transparency = transparency.clamp(0.0, 1.0);
+        self.ior = ior;
+        self
+    }
+
+    pub fn with_normal_map(mut self, texture: Arc<Texture>) -> Self {
+        self.surface_map = Some(SurfaceMap::Normal(texture));
+        self
+    }
+
+    pub fn with_bump_map(mut self, texture: Arc<Texture>, strength: f32) -> Self {
+        self.surface_map = Some(SurfaceMap::Bump { texture, strength });
+        self
+    }
+}
+
+impl SurfaceMap {
+    /// Normal perturbada en el punto `uv`. `tangent` y `bitangent` son las
+    /// direcciones (en mundo) en las que crecen u y v; no hace falta que sean
+    /// unitarias ni perpendiculares a `normal`.
+    pub fn perturb(&self, normal: &Vec3, tangent: &Vec3, bitangent: &Vec3, uv: &Vec2) -> Vec3 {
+        // Base ortonormal (Gram-Schmidt); sin tangentes útiles no se perturba
+        let t = tangent - normal * normal.dot(tangent);
+        if t.magnitude_squared() < 1e-12 {
+            return *normal;
+        }
+        let t = t.normalize();
+        let mut b = normal.cross(&t);
+        if b.dot(bitangent) < 0.0 {
+            b = -b; // UV espejadas
+        }
+
+        let perturbed = match self {
+            SurfaceMap::Normal(texture) => {
+                let c = texture.get_color(uv.x, uv.y);
+                t * (c.r * 2.0 - 1.0) + b * (c.g * 2.0 - 1.0) + normal * (c.b * 2.0 - 1.0)
+            }
+            SurfaceMap::Bump { texture, strength } => {
+                // Pendiente del mapa de alturas por diferencias de un texel
+                let (w, h) = texture.dimensions();
+                let du = 1.0 / w as f32;
+                let dv = 1.0 / h as f32;
+                let height = |u: f32, v: f32| texture.get_color(u, v).luminance();
+                let h0 = height(uv.x, uv.y);
+                let dh_du = height(uv.x + du, uv.y) - h0;
+                let dh_dv = height(uv.x, uv.y + dv) - h0;
+                normal - (t * dh_du + b * dh_dv) * *strength
+            }
+        };
+
+        if perturbed.magnitude_squared() < 1e-12 {
+            *normal
+        } else {
+            perturbed.normalize()
         }
     }
 }
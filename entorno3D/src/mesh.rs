@@ -0,0 +1,137 @@
+// mesh.rs
+// Malla de triángulos con normales y coordenadas UV por vértice
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::texture::{self, Texture};
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
+
+// Material compartido por todos los triángulos de una misma parte de la malla
+pub struct MeshMaterial {
+    pub material: Material,
+    pub texture: Option<Arc<Texture>>,
+}
+
+pub struct Triangle {
+    pub positions: [Vec3; 3],
+    pub normals: Option<[Vec3; 3]>, // si no hay, se usa la normal geométrica
+    pub uvs: Option<[Vec2; 3]>,
+    pub material: Arc<MeshMaterial>,
+}
+
+impl RayIntersect for Triangle {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        // Möller–Trumbore
+        let [p0, p1, p2] = self.positions;
+        let edge1 = p1 - p0;
+        let edge2 = p2 - p0;
+
+        let h = ray_direction.cross(&edge2);
+        let det = edge1.dot(&h);
+        if det.abs() < 1e-8 {
+            return Intersect::empty(); // rayo paralelo al triángulo
+        }
+
+        let inv_det = 1.0 / det;
+        let s = ray_origin - p0;
+        let u = inv_det * s.dot(&h);
+        if !(0.0..=1.0).contains(&u) {
+            return Intersect::empty();
+        }
+
+        let q = s.cross(&edge1);
+        let v = inv_det * ray_direction.dot(&q);
+        if v < 0.0 || u + v > 1.0 {
+            return Intersect::empty();
+        }
+
+        let t = inv_det * edge2.dot(&q);
+        if t < 1e-4 {
+            return Intersect::empty();
+        }
+
+        // Coordenadas baricéntricas del punto de impacto
+        let w = 1.0 - u - v;
+        let point = ray_origin + ray_direction * t;
+
+        let normal = match &self.normals {
+            Some([n0, n1, n2]) => (n0 * w + n1 * u + n2 * v).normalize(),
+            None => edge1.cross(&edge2).normalize(),
+        };
+
+        let mut hit = Intersect::new(point, normal, t, self.material.material.clone());
+
+        // Sin UV en el OBJ se usan las baricéntricas y las aristas como tangentes
+        hit = match &self.uvs {
+            Some([uv0, uv1, uv2]) => {
+                let (tangent, bitangent) = uv_tangents(&edge1, &edge2, &(uv1 - uv0), &(uv2 - uv0));
+                hit.with_surface(uv0 * w + uv1 * u + uv2 * v, tangent, bitangent)
+            }
+            None => hit.with_surface(Vec2::new(u, v), edge1, edge2),
+        };
+
+        if let (Some(tex), Some([uv0, uv1, uv2])) = (&self.material.texture, &self.uvs) {
+            let uv = hit.uv;
+            // Cuántas unidades de mundo abarca la textura en este triángulo
+            let world_area = edge1.cross(&edge2).magnitude();
+            let uv_e1 = uv1 - uv0;
+            let uv_e2 = uv2 - uv0;
+            let uv_area = (uv_e1.x * uv_e2.y - uv_e1.y * uv_e2.x).abs();
+            let texture_size = if uv_area > 0.0 {
+                (world_area / uv_area).sqrt()
+            } else {
+                1.0
+            };
+            let scale = texture::footprint_scale(ray_direction, &normal, texture_size);
+            hit = hit.with_texture(tex, uv, scale);
+        }
+
+        hit
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [p0, p1, p2] = &self.positions;
+        Aabb::new(p0.inf(&p1.inf(p2)), p0.sup(&p1.sup(p2)))
+    }
+}
+
+// Direcciones (dP/du, dP/dv) a partir de las aristas y sus diferencias de UV
+fn uv_tangents(edge1: &Vec3, edge2: &Vec3, duv1: &Vec2, duv2: &Vec2) -> (Vec3, Vec3) {
+    let det = duv1.x * duv2.y - duv1.y * duv2.x;
+    if det.abs() < 1e-12 {
+        return (*edge1, *edge2); // UV degeneradas
+    }
+    let r = 1.0 / det;
+    let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+    let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+    (tangent, bitangent)
+}
+
+/// Malla: los triángulos se organizan en su propio BVH
+pub struct Mesh {
+    triangles: Bvh<Triangle>,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<Triangle>) -> Self {
+        Mesh {
+            triangles: Bvh::new(triangles),
+        }
+    }
+
+    pub fn triangles(&self) -> &[Triangle] {
+        self.triangles.objects()
+    }
+}
+
+impl RayIntersect for Mesh {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.triangles.closest_hit(ray_origin, ray_direction)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.triangles.bounding_box()
+    }
+}
@@ -0,0 +1,141 @@
+// obj.rs
+// Carga de mallas Wavefront OBJ y de sus materiales MTL
+use crate::color::LinearColor;
+use crate::material::Material;
+use crate::mesh::{Mesh, MeshMaterial, Triangle};
+use crate::texture::Texture;
+use image::ImageError;
+use nalgebra_glm::{Vec2, Vec3};
+use std::path::Path;
+use std::sync::Arc;
+
+impl Mesh {
+    /// Carga un OBJ como una sola malla. Ver `load_obj`.
+    pub fn from_obj<P: AsRef<Path>>(path: P, fallback: Material) -> Result<Self, String> {
+        load_obj(path, fallback).map(Mesh::new)
+    }
+}
+
+/// Lee los triángulos de un archivo OBJ (las caras se triangulan).
+/// Los materiales del MTL se traducen a `Material` + `Texture` (map_Kd);
+/// map_Bump/bump se interpreta como normal map en espacio tangente.
+/// `fallback` se usa para las partes sin material y para los valores que el MTL no define.
+/// Las rutas de texturas se resuelven relativas a la carpeta del OBJ.
+pub fn load_obj<P: AsRef<Path>>(path: P, fallback: Material) -> Result<Vec<Triangle>, String> {
+    let path = path.as_ref();
+    let (models, materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)
+        .map_err(|e| format!("No se pudo cargar el OBJ {}: {}", path.display(), e))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    // Si el MTL falta o es inválido, todo usa el material de respaldo
+    let materials = materials
+        .unwrap_or_default()
+        .iter()
+        .map(|mtl| convert_material(mtl, base_dir, &fallback).map(Arc::new))
+        .collect::<Result<Vec<_>, _>>()?;
+    let default_material = Arc::new(MeshMaterial {
+        material: fallback,
+        texture: None,
+    });
+
+    let mut triangles = Vec::new();
+    for model in &models {
+        let mesh = &model.mesh;
+        let material = mesh
+            .material_id
+            .and_then(|id| materials.get(id))
+            .unwrap_or(&default_material);
+
+        let position = |i: usize| {
+            Vec3::new(
+                mesh.positions[3 * i],
+                mesh.positions[3 * i + 1],
+                mesh.positions[3 * i + 2],
+            )
+        };
+        let normal = |i: usize| {
+            Vec3::new(
+                mesh.normals[3 * i],
+                mesh.normals[3 * i + 1],
+                mesh.normals[3 * i + 2],
+            )
+        };
+        let uv = |i: usize| Vec2::new(mesh.texcoords[2 * i], mesh.texcoords[2 * i + 1]);
+
+        let has_normals = !mesh.normals.is_empty();
+        let has_uvs = !mesh.texcoords.is_empty();
+
+        for face in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [face[0] as usize, face[1] as usize, face[2] as usize];
+            triangles.push(Triangle {
+                positions: [position(a), position(b), position(c)],
+                normals: has_normals.then(|| [normal(a), normal(b), normal(c)]),
+                uvs: has_uvs.then(|| [uv(a), uv(b), uv(c)]),
+                material: Arc::clone(material),
+            });
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn convert_material(
+    mtl: &tobj::Material,
+    base_dir: &Path,
+    fallback: &Material,
+) -> Result<MeshMaterial, String> {
+    // Kd ya viene en lineal: no pasa por 8 bits ni por la curva sRGB
+    let diffuse = mtl
+        .diffuse
+        .map(|kd| LinearColor::new(kd[0], kd[1], kd[2]))
+        .unwrap_or(fallback.diffuse);
+    // Ns es el exponente de Phong; Ks (promedio) el peso especular
+    let specular = mtl.shininess.unwrap_or(fallback.specular);
+    let specular_weight = mtl
+        .specular
+        .map(|ks| (ks[0] + ks[1] + ks[2]) / 3.0)
+        .unwrap_or(fallback.albedo[1]);
+
+    let mut albedo = fallback.albedo;
+    albedo[1] = specular_weight;
+
+    let texture = match &mtl.diffuse_texture {
+        Some(file) => Some(Arc::new(load_texture(base_dir, file)?)),
+        None => None,
+    };
+
+    // d (opacidad) y Ni (índice de refracción)
+    let transparency = mtl
+        .dissolve
+        .map(|d| 1.0 - d)
+        .unwrap_or(fallback.transparency);
+    let ior = mtl.optical_density.unwrap_or(fallback.ior);
+
+    let mut material = Material {
+        diffuse,
+        specular,
+        albedo,
+        ..Material::black()
+    }
+    .with_transparency(transparency, ior);
+    material.surface_map = fallback.surface_map.clone();
+    // tobj guarda map_Bump/bump en normal_texture; los exportadores
+    // (Blender, por ejemplo) lo usan para normal maps
+    if let Some(file) = &mtl.normal_texture {
+        let full_path = base_dir.join(file);
+        let normal_map = Texture::load_raw(&full_path).map_err(|e| texture_error(&full_path, e))?;
+        material = material.with_normal_map(Arc::new(normal_map));
+    }
+
+    Ok(MeshMaterial { material, texture })
+}
+
+fn load_texture(base_dir: &Path, file: &str) -> Result<Texture, String> {
+    let full_path = base_dir.join(file);
+    Texture::load(&full_path).map_err(|e| texture_error(&full_path, e))
+}
+
+fn texture_error(path: &Path, error: ImageError) -> String {
+    format!("No se pudo cargar la textura {}: {}", path.display(), error)
+}
@@ -0,0 +1,271 @@
+// pathtracer.rs
+// Integrador Monte Carlo: en cada rebote se suma la luz directa de las luces
+// puntuales (con su prueba de sombra) y se sigue un solo rayo elegido según
+// la BRDF del material. Sustituye la luz ambiental constante de cast_ray por
+// luz indirecta real (sangrado de color, sombras suaves de contacto, etc.).
+use nalgebra_glm::Vec3;
+use rand::Rng;
+use rand::rngs::SmallRng;
+
+use crate::brdf;
+use crate::color::LinearColor;
+use crate::material::{PbrParams, ShadingModel};
+use crate::ray_intersect::{Intersect, RayCone};
+use crate::raytracer::{SHADOW_BIAS, for_each_light_sample, reflect, refract};
+use crate::scene::Scene;
+
+const MAX_BOUNCES: u32 = 8;
+
+// A partir de este rebote los caminos con poca energía se cortan al azar (ruleta rusa)
+const ROULETTE_START: u32 = 3;
+
+pub fn trace_path(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    mut cone: RayCone,
+    scene: &Scene,
+    rng: &mut SmallRng,
+) -> LinearColor {
+    let mut radiance = LinearColor::black();
+    let mut throughput = LinearColor::new(1.0, 1.0, 1.0);
+    let mut origin = *ray_origin;
+    let mut direction = ray_direction.normalize();
+
+    for bounce in 0..MAX_BOUNCES {
+        let mut intersect = scene.objects.closest_hit(&origin, &direction);
+        if !intersect.is_intersecting {
+            // El entorno también ilumina: es la luz que llega de todas partes
+            radiance += throughput * scene.environment.sample(&direction);
+            break;
+        }
+        intersect.set_ray_cone(cone);
+        cone = intersect.cone;
+
+        // Normales del lado desde el que llega el rayo
+        let view_dir = -direction;
+        let inside = intersect.normal.dot(&view_dir) < 0.0;
+        let side = if inside { -1.0 } else { 1.0 };
+        let geometric_normal = intersect.normal * side;
+        let normal = intersect.shading_normal() * side;
+
+        if let ShadingModel::Pbr(params) = &intersect.material.model {
+            radiance += throughput * params.emission * (1.0 - intersect.material.transparency);
+        }
+        radiance += throughput
+            * direct_light(
+                &intersect,
+                &normal,
+                &geometric_normal,
+                &view_dir,
+                scene,
+                bounce,
+                rng,
+            );
+
+        let Some((next_direction, weight)) =
+            sample_bsdf(&intersect, &normal, &view_dir, inside, rng)
+        else {
+            break;
+        };
+        throughput = throughput * weight;
+
+        if bounce >= ROULETTE_START {
+            let survival = throughput
+                .r
+                .max(throughput.g)
+                .max(throughput.b)
+                .clamp(0.05, 1.0);
+            if rng.random::<f32>() >= survival {
+                break;
+            }
+            throughput = throughput * (1.0 / survival);
+        }
+
+        // El siguiente rayo sale del lado de la superficie hacia el que apunta
+        let offset = if next_direction.dot(&geometric_normal) < 0.0 {
+            -geometric_normal
+        } else {
+            geometric_normal
+        };
+        origin = intersect.point + offset * SHADOW_BIAS;
+        direction = next_direction;
+    }
+
+    radiance
+}
+
+// Luz que llega directamente de cada luz (varios puntos si es de área)
+fn direct_light(
+    intersect: &Intersect,
+    normal: &Vec3,
+    geometric_normal: &Vec3,
+    view_dir: &Vec3,
+    scene: &Scene,
+    depth: u32,
+    rng: &mut SmallRng,
+) -> LinearColor {
+    let mut total = LinearColor::black();
+    for_each_light_sample(
+        &intersect.point,
+        geometric_normal,
+        scene,
+        depth,
+        rng,
+        |light_dir, light| {
+            // Luces detrás de la cara por la que llegó el rayo
+            if light_dir.dot(geometric_normal) > 0.0 {
+                total += surface_response(intersect, normal, view_dir, &light_dir) * light;
+            }
+        },
+    );
+    total
+}
+
+// Respuesta a una luz puntual de intensidad 1, con la misma escala que cast_ray
+// (la parte transparente del material no se ilumina)
+fn surface_response(
+    intersect: &Intersect,
+    normal: &Vec3,
+    view_dir: &Vec3,
+    light_dir: &Vec3,
+) -> LinearColor {
+    let material = &intersect.material;
+    let surface_color = material.diffuse;
+
+    let response = match &material.model {
+        ShadingModel::Phong => {
+            let diffuse = normal.dot(light_dir).clamp(0.0, 1.0);
+            let reflect_dir = reflect(&-light_dir, normal);
+            let specular = view_dir.dot(&reflect_dir).max(0.0).powf(material.specular);
+            surface_color * (material.albedo[0] * diffuse)
+                + LinearColor::new(1.0, 1.0, 1.0) * (material.albedo[1] * specular)
+        }
+        ShadingModel::Pbr(params) => {
+            brdf::cook_torrance(
+                normal,
+                view_dir,
+                light_dir,
+                surface_color,
+                params.metallic,
+                params.roughness,
+                material.ior,
+            ) * std::f32::consts::PI
+        }
+    };
+    response * (1.0 - material.transparency)
+}
+
+// Elige la dirección del siguiente rebote. Devuelve también el peso
+// (BRDF · cos / pdf) por el que se multiplica la energía del camino.
+fn sample_bsdf(
+    intersect: &Intersect,
+    normal: &Vec3,
+    view_dir: &Vec3,
+    inside: bool,
+    rng: &mut SmallRng,
+) -> Option<(Vec3, LinearColor)> {
+    // Con probabilidad `transparency` el camino cruza (o rebota en) la interfaz;
+    // el peso de cada rama se compensa con su probabilidad y queda igual
+    if rng.random::<f32>() < intersect.material.transparency {
+        return Some(sample_dielectric(
+            normal,
+            view_dir,
+            inside,
+            intersect.material.ior,
+            rng,
+        ));
+    }
+
+    match &intersect.material.model {
+        ShadingModel::Phong => sample_phong(intersect, normal, view_dir, rng),
+        ShadingModel::Pbr(params) => sample_pbr(intersect, normal, view_dir, params, rng),
+    }
+}
+
+// Reflexión o refracción, elegidas con la probabilidad de Fresnel
+fn sample_dielectric(
+    normal: &Vec3,
+    view_dir: &Vec3,
+    inside: bool,
+    ior: f32,
+    rng: &mut SmallRng,
+) -> (Vec3, LinearColor) {
+    let incident = -view_dir;
+    let eta = if inside { ior } else { 1.0 / ior };
+    let cos_i = normal.dot(view_dir).clamp(0.0, 1.0);
+    let fresnel = brdf::fresnel_dielectric(cos_i, eta);
+
+    let direction = match refract(&incident, normal, eta) {
+        Some(refracted) if rng.random::<f32>() >= fresnel => refracted,
+        _ => reflect(&incident, normal),
+    };
+    (direction, LinearColor::new(1.0, 1.0, 1.0))
+}
+
+// Phong: se elige entre difuso y espejo según los pesos de `albedo`.
+// Si suman más de 1 se normalizan para que los caminos no ganen energía.
+fn sample_phong(
+    intersect: &Intersect,
+    normal: &Vec3,
+    view_dir: &Vec3,
+    rng: &mut SmallRng,
+) -> Option<(Vec3, LinearColor)> {
+    let albedo = &intersect.material.albedo;
+    let weights = [albedo[0], albedo[2]];
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let scale = total.min(1.0);
+
+    let incident = -view_dir;
+    let pick = rng.random::<f32>() * total;
+    if pick < weights[0] {
+        let direction = brdf::sample_cosine_hemisphere(normal, (rng.random(), rng.random()));
+        let surface_color = intersect.material.diffuse;
+        Some((direction, surface_color * scale))
+    } else {
+        Some((
+            reflect(&incident, normal),
+            LinearColor::new(scale, scale, scale),
+        ))
+    }
+}
+
+// PBR: lóbulo especular (GGX) o difuso, con probabilidad según la energía de cada uno
+fn sample_pbr(
+    intersect: &Intersect,
+    normal: &Vec3,
+    view_dir: &Vec3,
+    params: &PbrParams,
+    rng: &mut SmallRng,
+) -> Option<(Vec3, LinearColor)> {
+    let base_color = intersect.material.diffuse;
+    let f0 = brdf::base_reflectance(base_color, params.metallic, intersect.material.ior);
+    let n_dot_v = normal.dot(view_dir).max(0.0);
+    let fresnel = brdf::fresnel_schlick_roughness(n_dot_v, f0, params.roughness);
+
+    let diffuse_albedo = LinearColor::new(1.0 - fresnel.r, 1.0 - fresnel.g, 1.0 - fresnel.b)
+        * base_color
+        * (1.0 - params.metallic);
+    let specular_energy = fresnel.luminance();
+    let diffuse_energy = diffuse_albedo.luminance();
+    if specular_energy + diffuse_energy <= 0.0 {
+        return None;
+    }
+    let specular_probability = specular_energy / (specular_energy + diffuse_energy);
+
+    if rng.random::<f32>() < specular_probability {
+        let half = brdf::sample_ggx_normal(normal, params.roughness, (rng.random(), rng.random()));
+        let direction = reflect(&-view_dir, &half).normalize();
+        let weight =
+            brdf::ggx_sample_weight(normal, view_dir, &direction, &half, f0, params.roughness);
+        Some((direction, weight * (1.0 / specular_probability)))
+    } else {
+        let direction = brdf::sample_cosine_hemisphere(normal, (rng.random(), rng.random()));
+        Some((
+            direction,
+            diffuse_albedo * (1.0 / (1.0 - specular_probability)),
+        ))
+    }
+}
@@ -1,9 +1,11 @@
 /**
  * Tronco de cono (frustum) como primitiva
  */
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Vec2, Vec3};
+use std::f32::consts::PI;
 
 use crate::{
+    aabb::Aabb,
     material::Material,
     ray_intersect::{Intersect, RayIntersect},
 };
@@ -17,85 +19,208 @@ pub struct Frustum {
     pub material: Material,
 }
 
-impl RayIntersect for Frustum {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_dir: &Vec3) -> Intersect {
-        // 🚧 Aquí pondrías la intersección real con frustum,
-        // para empezar puedes usar una aproximación con un cilindro
-        // (más fácil y suficiente para debug).
-        // Luego afinamos la geometría real.
-        let axis = Vec3::new(0.0, 1.0, 0.0);
-        let center = self.base + axis * (self.height * 0.5);
-        let radius = self.radius_bottom.max(self.radius_top);
-
-        // === fallback: bounding sphere ===
-        let l = center - ray_origin;
-        let tca = l.dot(ray_dir);
-        if tca < 0.0 {
-            return Intersect::empty();
-        }
-        let d2 = l.dot(&l) - tca * tca;
-        if d2 > radius * radius {
-            return Intersect::empty();
+// Distancia mínima para aceptar un impacto (evita auto-intersecciones)
+const HIT_EPSILON: f32 = 1e-4;
+
+impl Frustum {
+    // Variación del radio por unidad de altura: r(y) = radius_bottom + k * y
+    fn slope(&self) -> f32 {
+        (self.radius_top - self.radius_bottom) / self.height
+    }
+
+    // Superficie lateral: raíces de (x² + z²) - r(y)² = 0 con y dentro de [0, h]
+    // Devuelve la distancia y la normal en coordenadas locales (base en el origen)
+    fn lateral_hit(&self, origin: &Vec3, dir: &Vec3) -> Option<(f32, Vec3)> {
+        let k = self.slope();
+        let r0 = self.radius_bottom + k * origin.y;
+
+        let a = dir.x * dir.x + dir.z * dir.z - k * k * dir.y * dir.y;
+        let b = 2.0 * (origin.x * dir.x + origin.z * dir.z - k * dir.y * r0);
+        let c = origin.x * origin.x + origin.z * origin.z - r0 * r0;
+
+        let roots = if a.abs() < 1e-8 {
+            // Rayo paralelo a la generatriz: la ecuación queda lineal
+            if b.abs() < 1e-8 {
+                return None;
+            }
+            [-c / b, f32::INFINITY]
+        } else {
+            let disc = b * b - 4.0 * a * c;
+            if disc < 0.0 {
+                return None;
+            }
+            let sqrt_disc = disc.sqrt();
+            let t0 = (-b - sqrt_disc) / (2.0 * a);
+            let t1 = (-b + sqrt_disc) / (2.0 * a);
+            [t0.min(t1), t0.max(t1)]
+        };
+
+        roots.into_iter().find_map(|t| {
+            if !t.is_finite() || t < HIT_EPSILON {
+                return None;
+            }
+            let p = origin + dir * t;
+            if p.y < 0.0 || p.y > self.height {
+                return None;
+            }
+            // Gradiente de la superficie implícita
+            let r = self.radius_bottom + k * p.y;
+            let normal = Vec3::new(p.x, -k * r, p.z).normalize();
+            Some((t, normal))
+        })
+    }
+
+    // UV y tangentes en un punto local: cilíndricas en el costado, planas en las tapas
+    fn surface_frame(&self, local: &Vec3, normal: &Vec3) -> (Vec2, Vec3, Vec3) {
+        if normal.y.abs() > 1.0 - 1e-6 {
+            let r = self.radius_bottom.max(self.radius_top);
+            let uv = Vec2::new(local.x / (2.0 * r) + 0.5, local.z / (2.0 * r) + 0.5);
+            (uv, Vec3::x(), Vec3::z())
+        } else {
+            let uv = Vec2::new(
+                (local.x.atan2(local.z) / PI + 1.0) * 0.5,
+                local.y / self.height,
+            );
+            (uv, Vec3::new(local.z, 0.0, -local.x), Vec3::y())
         }
-        let thc = (radius * radius - d2).sqrt();
-        let t0 = tca - thc;
-        let t1 = tca + thc;
+    }
 
-        let distance = if t0 < 0.0 { t1 } else { t0 };
-        if distance < 0.0 {
-            return Intersect::empty();
+    // Tapa horizontal a la altura y (local) con el radio dado
+    fn cap_hit(
+        origin: &Vec3,
+        dir: &Vec3,
+        y: f32,
+        radius: f32,
+        normal: Vec3,
+    ) -> Option<(f32, Vec3)> {
+        if dir.y.abs() < 1e-8 {
+            return None;
         }
+        let t = (y - origin.y) / dir.y;
+        if t < HIT_EPSILON {
+            return None;
+        }
+        let p = origin + dir * t;
+        (p.x * p.x + p.z * p.z <= radius * radius).then_some((t, normal))
+    }
+
+    // Impacto más cercano (distancia y normal) sin armar el Intersect, para
+    // no clonar el material en los rayos que después se descartan
+    fn closest_hit(&self, ray_origin: &Vec3, ray_dir: &Vec3) -> Option<(f32, Vec3)> {
+        // Trabajamos con la base en el origen y el eje sobre +Y
+        let origin = ray_origin - self.base;
+
+        let candidates = [
+            self.lateral_hit(&origin, ray_dir),
+            Frustum::cap_hit(
+                &origin,
+                ray_dir,
+                0.0,
+                self.radius_bottom,
+                Vec3::new(0.0, -1.0, 0.0),
+            ),
+            Frustum::cap_hit(
+                &origin,
+                ray_dir,
+                self.height,
+                self.radius_top,
+                Vec3::new(0.0, 1.0, 0.0),
+            ),
+        ];
+
+        candidates
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
 
+    fn intersect_at(
+        &self,
+        ray_origin: &Vec3,
+        ray_dir: &Vec3,
+        distance: f32,
+        normal: Vec3,
+    ) -> Intersect {
         let point = ray_origin + ray_dir * distance;
-        let normal = (point - center).normalize();
-
-        Intersect {
-            is_intersecting: true,
-            distance,
-            point,
-            normal,
-            material: self.material,
+        let (uv, tangent, bitangent) = self.surface_frame(&(point - self.base), &normal);
+        Intersect::new(point, normal, distance, self.material.clone())
+            .with_surface(uv, tangent, bitangent)
+    }
+}
+
+impl RayIntersect for Frustum {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_dir: &Vec3) -> Intersect {
+        match self.closest_hit(ray_origin, ray_dir) {
+            Some((distance, normal)) => self.intersect_at(ray_origin, ray_dir, distance, normal),
+            None => Intersect::empty(),
         }
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let r = self.radius_bottom.max(self.radius_top);
+        Aabb::new(
+            self.base - Vec3::new(r, 0.0, r),
+            self.base + Vec3::new(r, self.height, r),
+        )
+    }
 }
 
 /// El peón: 2 frustums apilados
 pub struct Pawn {
-    pub base: Vec3,
-    pub scale: f32,
-    pub material: Material,
+    // Se arman una sola vez, en `new`
+    frustums: [Frustum; 2],
 }
 
-impl RayIntersect for Pawn {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_dir: &Vec3) -> Intersect {
+impl Pawn {
+    pub fn new(base: Vec3, scale: f32, material: Material) -> Self {
         // dimensiones relativas
-        let h1 = 1.0 * self.scale;
-        let h2 = 1.2 * self.scale;
+        let h1 = 1.0 * scale;
+        let h2 = 1.2 * scale;
 
         let bottom = Frustum {
-            base: self.base,
-            radius_bottom: 0.6 * self.scale,
-            radius_top: 0.3 * self.scale,
+            base,
+            radius_bottom: 0.6 * scale,
+            radius_top: 0.3 * scale,
             height: h1,
-            material: self.material,
+            material: material.clone(),
         };
 
         let top = Frustum {
-            base: self.base + Vec3::new(0.0, h1, 0.0),
-            radius_bottom: 0.3 * self.scale,
-            radius_top: 0.5 * self.scale,
+            base: base + Vec3::new(0.0, h1, 0.0),
+            radius_bottom: 0.3 * scale,
+            radius_top: 0.5 * scale,
             height: h2,
-            material: self.material,
+            material,
         };
 
-        // chequear intersección con ambos
-        let i1 = bottom.ray_intersect(ray_origin, ray_dir);
-        let i2 = top.ray_intersect(ray_origin, ray_dir);
+        Pawn {
+            frustums: [bottom, top],
+        }
+    }
+}
 
-        if i1.is_intersecting && (!i2.is_intersecting || i1.distance < i2.distance) {
-            i1
-        } else {
-            i2
+impl RayIntersect for Pawn {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_dir: &Vec3) -> Intersect {
+        // chequear intersección con ambos y armar solo el más cercano
+        let closest = self
+            .frustums
+            .iter()
+            .filter_map(|frustum| {
+                let (distance, normal) = frustum.closest_hit(ray_origin, ray_dir)?;
+                Some((frustum, distance, normal))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        match closest {
+            Some((frustum, distance, normal)) => {
+                frustum.intersect_at(ray_origin, ray_dir, distance, normal)
+            }
+            None => Intersect::empty(),
         }
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let [bottom, top] = &self.frustums;
+        bottom.bounding_box().union(&top.bounding_box())
+    }
 }
@@ -1,14 +1,16 @@
 // plane.rs
+use crate::aabb::Aabb;
 use crate::material::Material;
 use crate::ray_intersect::{Intersect, RayIntersect};
-use crate::texture::Texture;
-use nalgebra_glm::Vec3;
+use crate::texture::{self, Texture};
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
 
 pub struct Plane {
     pub point: Vec3,
     pub normal: Vec3,
     pub material: Material,
-    pub texture: Option<Texture>,
+    pub texture: Option<Arc<Texture>>,
     pub scale: f32, // Tamaño de repetición de la textura
 }
 
@@ -24,15 +26,25 @@ impl RayIntersect for Plane {
                 let u = hit_point.x / self.scale;
                 let v = hit_point.z / self.scale;
 
-                // Si hay textura, usamos el color de ella
-                let mut mat = self.material;
-                if let Some(tex) = &self.texture {
-                    mat.diffuse = tex.get_color(u, v);
-                }
+                // u crece con X y v con Z (proyectados sobre el plano)
+                let hit = Intersect::new(hit_point, self.normal, t, self.material.clone())
+                    .with_surface(Vec2::new(u, v), Vec3::x(), Vec3::z());
 
-                return Intersect::new(hit_point, self.normal, t, mat);
+                // Si hay textura, usamos el color de ella
+                return match &self.texture {
+                    Some(tex) => {
+                        let scale =
+                            texture::footprint_scale(ray_direction, &self.normal, self.scale);
+                        hit.with_texture(tex, Vec2::new(u, v), scale)
+                    }
+                    None => hit,
+                };
             }
         }
         Intersect::empty()
     }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::infinite()
+    }
 }
@@ -0,0 +1,80 @@
+// postprocess.rs
+// Paso del color lineal (HDR) que sale del sombreado al color de 8 bits del
+// framebuffer: exposición, tone mapping y codificación sRGB, en ese orden.
+use crate::color::{Color, LinearColor};
+
+/// Cómo se comprimen los valores mayores que 1 al rango de la pantalla
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMapping {
+    /// Recorta cada canal a [0, 1]: los brillos fuertes se saturan en blanco
+    #[default]
+    Clamp,
+    /// L / (1 + L) sobre la luminancia, conservando el tono del color
+    Reinhard,
+    /// Curva fílmica de ACES (aproximación de Narkowicz): contraste en los
+    /// medios tonos y un hombro suave hacia el blanco
+    Aces,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostProcess {
+    /// Exposición en pasos (EV): cada +1 duplica la luz
+    pub exposure: f32,
+    pub tone_mapping: ToneMapping,
+    /// Codifica el resultado con la curva sRGB, la inversa de la que se aplica
+    /// al leer colores de 8 bits. Apagado solo sirve para guardar valores lineales.
+    pub srgb: bool,
+}
+
+impl Default for PostProcess {
+    // Sin exposición ni tone mapping, con salida sRGB
+    fn default() -> Self {
+        PostProcess {
+            exposure: 0.0,
+            tone_mapping: ToneMapping::default(),
+            srgb: true,
+        }
+    }
+}
+
+impl PostProcess {
+    pub fn apply(&self, color: LinearColor) -> Color {
+        let exposed = color * self.exposure.exp2();
+        let mapped = match self.tone_mapping {
+            ToneMapping::Clamp => exposed,
+            ToneMapping::Reinhard => {
+                let luminance = exposed.luminance();
+                if luminance > 0.0 {
+                    exposed * (1.0 / (1.0 + luminance))
+                } else {
+                    exposed
+                }
+            }
+            ToneMapping::Aces => map_channels(exposed, aces),
+        };
+        let encoded = if self.srgb {
+            map_channels(mapped, srgb_encode)
+        } else {
+            mapped
+        };
+        encoded.to_color()
+    }
+}
+
+fn map_channels(color: LinearColor, f: impl Fn(f32) -> f32) -> LinearColor {
+    LinearColor::new(f(color.r), f(color.g), f(color.b))
+}
+
+fn aces(x: f32) -> f32 {
+    let x = x.max(0.0);
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+fn srgb_encode(x: f32) -> f32 {
+    let x = x.clamp(0.0, 1.0);
+    if x <= 0.003_130_8 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -1,8 +1,44 @@
 //ray_intersect.rs
+use crate::aabb::Aabb;
 use crate::material::Material;
-use nalgebra_glm::Vec3;
+use crate::texture::{FilterMode, Texture};
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy)]
+/// Cono que cubre un píxel alrededor de un rayo: ancho en el origen y cuánto
+/// crece por unidad de distancia. Con él se elige el nivel de mipmap.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RayCone {
+    pub width: f32,
+    pub spread: f32,
+}
+
+impl RayCone {
+    pub fn width_at(&self, distance: f32) -> f32 {
+        self.width + self.spread * distance
+    }
+
+    /// El mismo cono, continuado desde un impacto a `distance` (para los
+    /// rayos reflejados o refractados ahí)
+    pub fn continued(&self, distance: f32) -> RayCone {
+        RayCone {
+            width: self.width_at(distance),
+            spread: self.spread,
+        }
+    }
+}
+
+// Textura trilineal pendiente de refinar: el nivel de mipmap depende del
+// ancho del píxel, que solo conoce quien lanzó el rayo (ver `set_ray_cone`)
+#[derive(Debug, Clone)]
+pub(crate) struct TextureHit {
+    pub(crate) texture: Arc<Texture>,
+    pub(crate) uv: Vec2,
+    // Ver texture::footprint_scale
+    pub(crate) footprint_scale: f32,
+}
+
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Intersect {
     pub point: Vec3,
@@ -10,6 +46,14 @@ pub struct Intersect {
     pub distance: f32,
     pub is_intersecting: bool,
     pub material: Material,
+    // Coordenadas de textura y direcciones en las que crecen u y v
+    // (para normal/bump maps; cero si la primitiva no las define)
+    pub uv: Vec2,
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+    pub(crate) texture: Option<TextureHit>,
+    // Cono del rayo que llegó, continuado desde el impacto
+    pub cone: RayCone,
 }
 
 impl Intersect {
@@ -20,6 +64,54 @@ impl Intersect {
             distance,
             is_intersecting: true,
             material,
+            uv: Vec2::zeros(),
+            tangent: Vec3::zeros(),
+            bitangent: Vec3::zeros(),
+            texture: None,
+            cone: RayCone::default(),
+        }
+    }
+
+    pub fn with_surface(mut self, uv: Vec2, tangent: Vec3, bitangent: Vec3) -> Self {
+        self.uv = uv;
+        self.tangent = tangent;
+        self.bitangent = bitangent;
+        self
+    }
+
+    /// Reemplaza `material.diffuse` por el color de `texture` en `uv`, con
+    /// la resolución completa. `footprint_scale` (ver texture::footprint_scale)
+    /// permite que `set_ray_cone` elija después un mipmap más adecuado.
+    pub fn with_texture(mut self, texture: &Arc<Texture>, uv: Vec2, footprint_scale: f32) -> Self {
+        self.material.diffuse = texture.get_color(uv.x, uv.y);
+        // Solo el filtro trilineal depende del tamaño del píxel
+        if texture.filter == FilterMode::Trilinear {
+            self.texture = Some(TextureHit {
+                texture: Arc::clone(texture),
+                uv,
+                footprint_scale,
+            });
+        }
+        self
+    }
+
+    /// Registra el cono del rayo que encontró este impacto: vuelve a muestrear
+    /// la textura de color con el ancho del píxel en este punto y deja el cono
+    /// continuado en `cone` para los rayos secundarios
+    pub fn set_ray_cone(&mut self, cone: RayCone) {
+        let width = cone.width_at(self.distance);
+        if let Some(hit) = self.texture.take() {
+            let footprint = width * hit.footprint_scale;
+            self.material.diffuse = hit.texture.sample(hit.uv.x, hit.uv.y, footprint);
+        }
+        self.cone = cone.continued(self.distance);
+    }
+
+    // Normal para sombrear: la geométrica, perturbada por el mapa del material si lo hay
+    pub fn shading_normal(&self) -> Vec3 {
+        match &self.material.surface_map {
+            Some(map) => map.perturb(&self.normal, &self.tangent, &self.bitangent, &self.uv),
+            None => self.normal,
         }
     }
 
@@ -30,10 +122,29 @@ impl Intersect {
             distance: 0.0,
             is_intersecting: false,
             material: Material::black(),
+            uv: Vec2::zeros(),
+            tangent: Vec3::zeros(),
+            bitangent: Vec3::zeros(),
+            texture: None,
+            cone: RayCone::default(),
         }
     }
 }
 
-pub trait RayIntersect {
+// Send + Sync: la escena se comparte (solo lectura) entre los hilos del render
+pub trait RayIntersect: Send + Sync {
     fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
+
+    // Caja envolvente en espacio de mundo (Aabb::infinite() si no tiene límites)
+    fn bounding_box(&self) -> Aabb;
+}
+
+impl<T: RayIntersect + ?Sized> RayIntersect for Box<T> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        (**self).ray_intersect(ray_origin, ray_direction)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
 }
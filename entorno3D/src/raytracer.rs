@@ -0,0 +1,615 @@
+// raytracer.rs
+use nalgebra_glm::Vec3;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::f32::consts::PI;
+
+use crate::brdf;
+use crate::bvh::Bvh;
+use crate::color::LinearColor;
+use crate::filter::{FilteredSample, PixelFilter};
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::material::{PbrParams, ShadingModel};
+use crate::pathtracer;
+use crate::postprocess::PostProcess;
+use crate::ray_intersect::{Intersect, RayCone};
+use crate::render_target::RenderTarget;
+use crate::scene::Scene;
+
+pub(crate) const SHADOW_BIAS: f32 = 1e-4;
+
+pub(crate) fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
+    incident - 2.0 * incident.dot(normal) * normal
+}
+
+pub(crate) fn refract(incident: &Vec3, normal: &Vec3, eta: f32) -> Option<Vec3> {
+    let n_dot_i = normal.dot(incident);
+    let k = 1.0 - eta * eta * (1.0 - n_dot_i * n_dot_i);
+
+    if k < 0.0 {
+        None // Reflexión interna total
+    } else {
+        Some(eta * incident - (eta * n_dot_i + k.sqrt()) * normal)
+    }
+}
+
+// Normal geométrica del lado desde el que llega `incident`
+fn arrival_side(incident: &Vec3, geometric: &Vec3) -> Vec3 {
+    if incident.dot(geometric) < 0.0 {
+        *geometric
+    } else {
+        -geometric
+    }
+}
+
+/// Reflejo de `incident` en una superficie con normal de sombreado `normal`
+/// y normal geométrica `geometric`. Con un bump o normal map fuerte el reflejo
+/// respecto de `normal` puede apuntar hacia la misma superficie; en ese caso
+/// se refleja respecto de la normal geométrica.
+pub fn reflect_on_surface(incident: &Vec3, normal: &Vec3, geometric: &Vec3) -> Vec3 {
+    let reflected = reflect(incident, normal);
+    if reflected.dot(&arrival_side(incident, geometric)) > 0.0 {
+        reflected
+    } else {
+        reflect(incident, geometric)
+    }
+}
+
+/// Refracción de `incident` a través de la superficie (`normal` apunta hacia
+/// el lado de llegada). Si la normal de sombreado deja el rayo del lado de
+/// llegada, se refracta respecto de la geométrica. None con reflexión interna total.
+pub fn refract_through_surface(
+    incident: &Vec3,
+    normal: &Vec3,
+    geometric: &Vec3,
+    eta: f32,
+) -> Option<Vec3> {
+    let side = arrival_side(incident, geometric);
+    match refract(incident, normal, eta) {
+        Some(refracted) if refracted.dot(&side) < 0.0 => Some(refracted),
+        _ => refract(incident, &side, eta),
+    }
+}
+
+/// Un reflejo desviado al azar (jitter, microfacetas) que cruzó la superficie
+/// geométrica se espeja en su plano para que vuelva al lado de `incident`
+pub fn keep_reflection_outside(incident: &Vec3, direction: &Vec3, geometric: &Vec3) -> Vec3 {
+    let side = arrival_side(incident, geometric);
+    let d = direction.dot(&side);
+    if d > 0.0 {
+        *direction
+    } else {
+        direction - side * (2.0 * d)
+    }
+}
+
+/// ¿Hay algo entre `point` y `light_position`? El origen del rayo de sombra
+/// se separa de la superficie hacia el lado de la luz.
+pub(crate) fn is_occluded(
+    point: &Vec3,
+    normal: &Vec3,
+    light_position: &Vec3,
+    objects: &Bvh,
+) -> bool {
+    let to_light = light_position - point;
+    let light_distance = to_light.magnitude();
+    let light_dir = to_light / light_distance;
+
+    let offset_normal = normal * SHADOW_BIAS;
+    let shadow_ray_origin = if light_dir.dot(normal) < 0.0 {
+        point - offset_normal
+    } else {
+        point + offset_normal
+    };
+
+    objects
+        .any_hit(&shadow_ray_origin, &light_dir, light_distance)
+        .is_some()
+}
+
+/// Rayos de sombra por luz: todos los de la luz en el primer impacto y uno
+/// solo en los rebotes, donde la penumbra apenas se nota y el costo se multiplica
+pub(crate) fn shadow_samples(light: &Light, depth: u32) -> u32 {
+    if depth == 0 { light.samples } else { 1 }
+}
+
+/// Llama a `f(light_dir, light_color * intensity)` por cada rayo de sombra
+/// que llega a alguna luz desde `point`. Las luces de área se muestrean con
+/// varios rayos (ver `shadow_samples`); cada uno aporta una fracción de la intensidad.
+pub(crate) fn for_each_light_sample(
+    point: &Vec3,
+    normal: &Vec3,
+    scene: &Scene,
+    depth: u32,
+    rng: &mut SmallRng,
+    mut f: impl FnMut(Vec3, LinearColor),
+) {
+    for light in &scene.lights {
+        let samples = shadow_samples(light, depth);
+        let light_color = LinearColor::from(light.color) * (light.intensity / samples as f32);
+
+        for _ in 0..samples {
+            let light_position = light.sample_position(point, rng);
+            if is_occluded(point, normal, &light_position, &scene.objects) {
+                continue;
+            }
+            f((light_position - point).normalize(), light_color);
+        }
+    }
+}
+
+pub fn cast_ray(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    cone: RayCone,
+    scene: &Scene,
+    depth: u32,
+    rng: &mut SmallRng,
+) -> LinearColor {
+    const MAX_DEPTH: u32 = 5;
+
+    // Sin más rebotes (o sin impacto) se ve el entorno en esa dirección
+    if depth > MAX_DEPTH {
+        return scene.environment.sample(ray_direction);
+    }
+
+    let mut intersect = scene.objects.closest_hit(ray_origin, ray_direction);
+
+    if !intersect.is_intersecting {
+        return scene.environment.sample(ray_direction);
+    }
+    intersect.set_ray_cone(cone);
+
+    // Normal perturbada (normal/bump map) para las direcciones de sombreado;
+    // los desplazamientos de los rayos secundarios usan la geométrica
+    let normal = intersect.shading_normal();
+
+    let surface = match &intersect.material.model {
+        ShadingModel::Phong => shade_phong(ray_direction, &intersect, &normal, scene, depth, rng),
+        ShadingModel::Pbr(params) => shade_pbr(
+            ray_direction,
+            &intersect,
+            &normal,
+            params,
+            scene,
+            depth,
+            rng,
+        ),
+    };
+
+    // La parte transparente no se sombrea: deja pasar (o refleja) la luz
+    let transparency = intersect.material.transparency;
+    if transparency <= 0.0 {
+        return surface;
+    }
+    let transmitted = cast_dielectric(ray_direction, &intersect, &normal, scene, depth, rng);
+    surface * (1.0 - transparency) + transmitted * transparency
+}
+
+// Interfaz entre dos medios: reflexión y refracción mezcladas según Fresnel.
+// Con reflexión interna total toda la luz sigue el rayo reflejado.
+fn cast_dielectric(
+    ray_direction: &Vec3,
+    intersect: &Intersect,
+    normal: &Vec3,
+    scene: &Scene,
+    depth: u32,
+    rng: &mut SmallRng,
+) -> LinearColor {
+    let direction = ray_direction.normalize();
+    let entering = direction.dot(&intersect.normal) < 0.0;
+    let ior = intersect.material.ior;
+    let (eta, facing, geometric) = if entering {
+        (1.0 / ior, *normal, intersect.normal)
+    } else {
+        (ior, -normal, -intersect.normal)
+    };
+
+    let cos_i = (-direction.dot(&facing)).clamp(0.0, 1.0);
+    let fresnel = brdf::fresnel_dielectric(cos_i, eta);
+
+    let reflected_dir = reflect_on_surface(&direction, &facing, &intersect.normal);
+    let reflection_origin = intersect.point + geometric * SHADOW_BIAS;
+    let reflection = cast_ray(
+        &reflection_origin,
+        &reflected_dir,
+        intersect.cone,
+        scene,
+        depth + 1,
+        rng,
+    );
+
+    match refract_through_surface(&direction, &facing, &intersect.normal, eta) {
+        Some(refracted_dir) if fresnel < 1.0 => {
+            let refraction_origin = intersect.point - geometric * SHADOW_BIAS;
+            let refraction = cast_ray(
+                &refraction_origin,
+                &refracted_dir,
+                intersect.cone,
+                scene,
+                depth + 1,
+                rng,
+            );
+            reflection * fresnel + refraction * (1.0 - fresnel)
+        }
+        _ => reflection,
+    }
+}
+
+// Modelo clásico: Phong + reflexión ponderada por `albedo`
+fn shade_phong(
+    ray_direction: &Vec3,
+    intersect: &Intersect,
+    normal: &Vec3,
+    scene: &Scene,
+    depth: u32,
+    rng: &mut SmallRng,
+) -> LinearColor {
+    let view_dir = -ray_direction.normalize();
+    let surface_color = intersect.material.diffuse;
+
+    // Aporte difuso y especular de cada luz
+    let mut diffuse = LinearColor::black();
+    let mut specular = LinearColor::black();
+    for_each_light_sample(
+        &intersect.point,
+        &intersect.normal,
+        scene,
+        depth,
+        rng,
+        |light_dir, light| {
+            let reflect_dir = reflect(&-light_dir, normal);
+
+            let diffuse_intensity = normal.dot(&light_dir).clamp(0.0, 1.0);
+            diffuse += surface_color * light * intersect.material.albedo[0] * diffuse_intensity;
+
+            let specular_intensity = view_dir
+                .dot(&reflect_dir)
+                .max(0.0)
+                .powf(intersect.material.specular);
+            specular += light * intersect.material.albedo[1] * specular_intensity;
+        },
+    );
+
+    let mut reflection_color = LinearColor::black();
+    if intersect.material.albedo[2] > 0.0 {
+        let reflected_dir = reflect_on_surface(ray_direction, normal, &intersect.normal);
+        let reflection_origin =
+            intersect.point + arrival_side(ray_direction, &intersect.normal) * SHADOW_BIAS;
+        reflection_color = cast_ray(
+            &reflection_origin,
+            &reflected_dir,
+            intersect.cone,
+            scene,
+            depth + 1,
+            rng,
+        ) * intersect.material.albedo[2];
+    }
+
+    let mut reflected_light_color = LinearColor::black();
+    if intersect.material.albedo[2] > 0.0 {
+        let reflected_dir = reflect_on_surface(ray_direction, normal, &intersect.normal);
+        let reflection_origin =
+            intersect.point + arrival_side(ray_direction, &intersect.normal) * SHADOW_BIAS;
+
+        for _ in 0..3 {
+            let jitter = Vec3::new(
+                rng.random::<f32>() - 0.5,
+                rng.random::<f32>() - 0.5,
+                rng.random::<f32>() - 0.5,
+            ) * 0.1;
+
+            let jittered_dir = keep_reflection_outside(
+                ray_direction,
+                &(reflected_dir + jitter).normalize(),
+                &intersect.normal,
+            );
+
+            reflected_light_color += cast_ray(
+                &reflection_origin,
+                &jittered_dir,
+                intersect.cone,
+                scene,
+                depth + 1,
+                rng,
+            );
+        }
+
+        reflected_light_color = reflected_light_color * (1.0 / 3.0) * intersect.material.albedo[2];
+    }
+    // === 🔥 Luz ambiental ===
+    let ambient_strength = 0.22; // controla qué tan fuerte es la luz ambiente
+    let ambient = surface_color * ambient_strength;
+
+    diffuse + specular + reflection_color + reflected_light_color + ambient
+}
+
+// Modelo metálico-rugoso: Cook-Torrance/GGX para cada luz, un rayo reflejado
+// (muestreado según la rugosidad) ponderado por Fresnel, y la emisión
+fn shade_pbr(
+    ray_direction: &Vec3,
+    intersect: &Intersect,
+    normal: &Vec3,
+    params: &PbrParams,
+    scene: &Scene,
+    depth: u32,
+    rng: &mut SmallRng,
+) -> LinearColor {
+    let view_dir = -ray_direction.normalize();
+    let base_color = intersect.material.diffuse;
+
+    let mut direct = LinearColor::black();
+    for_each_light_sample(
+        &intersect.point,
+        &intersect.normal,
+        scene,
+        depth,
+        rng,
+        |light_dir, light| {
+            let brdf = brdf::cook_torrance(
+                normal,
+                &view_dir,
+                &light_dir,
+                base_color,
+                params.metallic,
+                params.roughness,
+                intersect.material.ior,
+            );
+            // × π para que un blanco mate reciba lo mismo que con Phong (albedo 1)
+            direct += brdf * light * PI;
+        },
+    );
+
+    // Reflejo del entorno: una normal de microfaceta por rayo, así las
+    // superficies rugosas se ven borrosas al acumular muestras
+    let n_dot_v = normal.dot(&view_dir).max(0.0);
+    let f0 = brdf::base_reflectance(base_color, params.metallic, intersect.material.ior);
+    let fresnel = brdf::fresnel_schlick_roughness(n_dot_v, f0, params.roughness);
+
+    let microfacet =
+        brdf::sample_ggx_normal(normal, params.roughness, (rng.random(), rng.random()));
+    let reflected_dir = keep_reflection_outside(
+        ray_direction,
+        &reflect(ray_direction, &microfacet).normalize(),
+        &intersect.normal,
+    );
+    let reflection_origin =
+        intersect.point + arrival_side(ray_direction, &intersect.normal) * SHADOW_BIAS;
+    let reflection = cast_ray(
+        &reflection_origin,
+        &reflected_dir,
+        intersect.cone,
+        scene,
+        depth + 1,
+        rng,
+    ) * fresnel;
+
+    // Misma luz ambiental que Phong, solo para la parte difusa
+    let ambient_strength = 0.22;
+    let diffuse_weight = LinearColor::new(1.0 - fresnel.r, 1.0 - fresnel.g, 1.0 - fresnel.b)
+        * (1.0 - params.metallic);
+    let ambient = base_color * diffuse_weight * ambient_strength;
+
+    direct + reflection + ambient + params.emission
+}
+
+/// Opciones de un render completo
+#[derive(Debug, Clone, Copy)]
+pub struct RenderSettings {
+    /// Semilla base para los números aleatorios (jitter de reflexiones).
+    /// Cada píxel deriva su propia semilla, así que el resultado no depende
+    /// del número de hilos ni del orden en que se procesan las filas.
+    pub seed: u64,
+    /// Reparte las filas del framebuffer entre varios hilos con rayon
+    pub parallel: bool,
+    pub integrator: Integrator,
+    /// Rayos por píxel en cada pasada, repartidos (estratificados) dentro del píxel
+    pub samples_per_pixel: u32,
+    /// Cómo se ponderan las muestras de un píxel
+    pub filter: PixelFilter,
+    /// Desplaza al azar la muestra aunque haya una sola por píxel. Con una sola
+    /// muestra, sin jitter el rayo pasa por el centro; el render progresivo lo
+    /// activa para que el antialiasing salga de las pasadas acumuladas, que
+    /// conservan el peso del filtro de cada muestra (ver `FilteredSample`).
+    pub jitter: bool,
+    /// Exposición, tone mapping y gamma al pasar a 8 bits
+    pub post: PostProcess,
+}
+
+/// Algoritmo con el que se calcula el color de cada rayo de cámara
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// `cast_ray`: Whitted con luz ambiental constante (rápido, sin ruido difuso)
+    #[default]
+    Whitted,
+    /// `pathtracer::trace_path`: Monte Carlo con luz indirecta
+    PathTracer,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            seed: 0,
+            parallel: true,
+            integrator: Integrator::default(),
+            samples_per_pixel: 1,
+            filter: PixelFilter::default(),
+            jitter: false,
+            post: PostProcess::default(),
+        }
+    }
+}
+
+// Semilla determinista por píxel a partir de la semilla del render
+fn pixel_seed(seed: u64, x: usize, y: usize, width: usize) -> u64 {
+    seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (y * width + x) as u64
+}
+
+/// Renderiza `scene` desde `scene.camera`
+pub fn render(framebuffer: &mut Framebuffer, scene: &Scene, settings: &RenderSettings) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+
+    render_rows(
+        &mut framebuffer.buffer,
+        width,
+        height,
+        scene,
+        settings,
+        // Única conversión a 8 bits: al escribir en el framebuffer
+        |pixel, sample| *pixel = settings.post.apply(sample.resolve()).to_hex(),
+    );
+}
+
+/// Suma una pasada más por píxel a `accumulation` (width * height sumas
+/// ponderadas). Para render progresivo: llamar con una semilla distinta en
+/// cada pasada y usar `FilteredSample::resolve` para obtener el promedio.
+pub fn accumulate(
+    accumulation: &mut [FilteredSample],
+    width: usize,
+    height: usize,
+    scene: &Scene,
+    settings: &RenderSettings,
+) {
+    render_rows(
+        accumulation,
+        width,
+        height,
+        scene,
+        settings,
+        |pixel, sample| *pixel += sample,
+    );
+}
+
+/// Llena profundidad, normal e ID de objeto de `target` con el rayo que pasa
+/// por el centro de cada píxel (sin antialiasing: son datos de geometría).
+/// `target` debe haberse creado con `RenderTarget::with_geometry`.
+pub fn render_geometry(target: &mut RenderTarget, scene: &Scene, settings: &RenderSettings) {
+    let width = target.width;
+    let height = target.height;
+    let aspect_ratio = width as f32 / height as f32;
+    let camera = &scene.camera;
+
+    let sample = |index: usize, depth: &mut f32, normal: &mut Vec3, object_id: &mut Option<u32>| {
+        let (x, y) = (index % width, index / width);
+        let mut rng = SmallRng::seed_from_u64(pixel_seed(settings.seed, x, y, width));
+        let (screen_x, screen_y) =
+            screen_point(x as f32 + 0.5, y as f32 + 0.5, width as f32, height as f32);
+
+        *depth = f32::INFINITY;
+        *normal = Vec3::zeros();
+        *object_id = None;
+        let Some((origin, direction)) =
+            camera.primary_ray(screen_x, screen_y, aspect_ratio, &mut rng)
+        else {
+            return;
+        };
+        let (intersect, object) = scene.objects.closest_hit_object(&origin, &direction);
+        if intersect.is_intersecting {
+            *depth = intersect.distance;
+            *normal = intersect.shading_normal();
+            *object_id = object.map(|index| index as u32);
+        }
+    };
+
+    // Cada canal se escribe en su lugar, sin buffers intermedios
+    let (depth, normal, object_id) = (&mut target.depth, &mut target.normal, &mut target.object_id);
+    if settings.parallel {
+        depth
+            .par_iter_mut()
+            .zip(normal.par_iter_mut())
+            .zip(object_id.par_iter_mut())
+            .enumerate()
+            .for_each(|(index, ((d, n), id))| sample(index, d, n, id));
+    } else {
+        depth
+            .iter_mut()
+            .zip(normal.iter_mut())
+            .zip(object_id.iter_mut())
+            .enumerate()
+            .for_each(|(index, ((d, n), id))| sample(index, d, n, id));
+    }
+}
+
+// Punto de la pantalla en [-1, 1] (y hacia arriba) para una posición en píxeles
+fn screen_point(x: f32, y: f32, width: f32, height: f32) -> (f32, f32) {
+    ((2.0 * x) / width - 1.0, -(2.0 * y) / height + 1.0)
+}
+
+// Recorre el buffer fila por fila y guarda el color de cada píxel con `store`
+fn render_rows<T: Send>(
+    buffer: &mut [T],
+    width: usize,
+    height: usize,
+    scene: &Scene,
+    settings: &RenderSettings,
+    store: impl Fn(&mut T, FilteredSample) + Sync,
+) {
+    // Cada fila es independiente: la escena solo se lee
+    let render_row = |(y, row): (usize, &mut [T])| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let mut rng = SmallRng::seed_from_u64(pixel_seed(settings.seed, x, y, width));
+            let pixel_color = render_pixel(x, y, width, height, scene, settings, &mut rng);
+            store(pixel, pixel_color);
+        }
+    };
+
+    if settings.parallel {
+        buffer
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(render_row);
+    } else {
+        buffer.chunks_mut(width).enumerate().for_each(render_row);
+    }
+}
+
+fn render_pixel(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    scene: &Scene,
+    settings: &RenderSettings,
+    rng: &mut SmallRng,
+) -> FilteredSample {
+    let camera = &scene.camera;
+    let width = width as f32;
+    let height = height as f32;
+    let aspect_ratio = width / height;
+
+    let samples = settings.samples_per_pixel.max(1);
+    let jitter = samples > 1 || settings.jitter;
+    let cone = camera.pixel_cone(width as usize, height as usize, samples);
+
+    let mut pixel = FilteredSample::empty();
+    for i in 0..samples {
+        let (dx, dy) = if jitter {
+            settings.filter.sample_offset(i, samples, rng)
+        } else {
+            (0.0, 0.0)
+        };
+        let weight = settings.filter.weight(dx, dy);
+        if weight <= 0.0 {
+            continue;
+        }
+
+        let (screen_x, screen_y) =
+            screen_point(x as f32 + 0.5 + dx, y as f32 + 0.5 + dy, width, height);
+
+        // Fuera de la imagen del ojo de pez el píxel queda negro
+        let sample = match camera.primary_ray(screen_x, screen_y, aspect_ratio, rng) {
+            Some((origin, direction)) => match settings.integrator {
+                Integrator::Whitted => cast_ray(&origin, &direction, cone, scene, 0, rng),
+                Integrator::PathTracer => {
+                    pathtracer::trace_path(&origin, &direction, cone, scene, rng)
+                }
+            },
+            None => LinearColor::black(),
+        };
+        pixel.add(sample, weight);
+    }
+    pixel
+}
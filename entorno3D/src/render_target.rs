@@ -0,0 +1,152 @@
+// render_target.rs
+// Destino de render con varios canales por píxel: la suma de color en punto
+// flotante (para render progresivo y post-proceso) y un G-buffer con la
+// profundidad, la normal y el objeto que ve el rayo central de cada píxel
+// (para denoisers o efectos que necesitan la geometría).
+use crate::color::LinearColor;
+use crate::filter::FilteredSample;
+use crate::framebuffer::Framebuffer;
+use crate::postprocess::PostProcess;
+use crate::raytracer::{RenderSettings, accumulate, render_geometry};
+use crate::scene::Scene;
+use image::{ImageResult, Rgb, RgbImage};
+use nalgebra_glm::Vec3;
+use std::path::Path;
+
+pub struct RenderTarget {
+    pub width: usize,
+    pub height: usize,
+    /// Suma de las pasadas de color, ponderada por el filtro de píxel
+    pub color: Vec<FilteredSample>,
+    /// Pasadas sumadas hasta ahora; cada una aporta
+    /// `RenderSettings::samples_per_pixel` muestras por píxel
+    pub passes: u32,
+    /// Si se llenan los canales de geometría (ver `with_geometry`); si no,
+    /// quedan vacíos y no cuestan nada
+    pub geometry: bool,
+    /// `Intersect::distance` del rayo central; infinito si no golpea nada
+    pub depth: Vec<f32>,
+    /// Normal de sombreado en espacio de mundo; cero si no golpea nada
+    pub normal: Vec<Vec3>,
+    /// Índice del objeto en `Scene::objects`
+    pub object_id: Vec<Option<u32>>,
+}
+
+impl RenderTarget {
+    /// Solo color
+    pub fn new(width: usize, height: usize) -> Self {
+        RenderTarget {
+            width,
+            height,
+            color: vec![FilteredSample::empty(); width * height],
+            passes: 0,
+            geometry: false,
+            depth: Vec::new(),
+            normal: Vec::new(),
+            object_id: Vec::new(),
+        }
+    }
+
+    /// Agrega profundidad, normales e IDs. Cuesta un rayo más por píxel al
+    /// empezar a acumular, así que solo conviene si alguien lee esos canales.
+    pub fn with_geometry(mut self) -> Self {
+        let size = self.width * self.height;
+        self.geometry = true;
+        self.depth = vec![f32::INFINITY; size];
+        self.normal = vec![Vec3::zeros(); size];
+        self.object_id = vec![None; size];
+        self
+    }
+
+    // Vacía todos los canales (llamar cuando la cámara o la escena cambian)
+    pub fn clear(&mut self) {
+        self.color.fill(FilteredSample::empty());
+        self.passes = 0;
+        self.depth.fill(f32::INFINITY);
+        self.normal.fill(Vec3::zeros());
+        self.object_id.fill(None);
+    }
+
+    /// Suma una pasada de color con `settings`. Con geometría, la primera
+    /// pasada también llena profundidad, normales e IDs, que no cambian
+    /// entre pasadas.
+    pub fn add_pass(&mut self, scene: &Scene, settings: &RenderSettings) {
+        if self.geometry && self.passes == 0 {
+            render_geometry(self, scene, settings);
+        }
+        accumulate(&mut self.color, self.width, self.height, scene, settings);
+        self.passes += 1;
+    }
+
+    /// Color promedio del píxel `index` (fila por fila)
+    pub fn average(&self, index: usize) -> LinearColor {
+        self.color[index].resolve()
+    }
+
+    /// Escribe el promedio en el framebuffer (del mismo tamaño).
+    /// El post-proceso se aplica al promedio, nunca a las muestras sueltas.
+    pub fn resolve(&self, framebuffer: &mut Framebuffer, post: &PostProcess) {
+        assert_eq!(
+            (framebuffer.width, framebuffer.height),
+            (self.width, self.height),
+            "el framebuffer y el render target deben tener el mismo tamaño"
+        );
+        if self.passes == 0 {
+            return;
+        }
+
+        for (index, pixel) in framebuffer.buffer.iter_mut().enumerate() {
+            *pixel = post.apply(self.average(index)).to_hex();
+        }
+    }
+
+    // Profundidad en grises: blanco lo más cercano, negro lo más lejano o el fondo
+    pub fn save_depth<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        let finite = self.depth.iter().copied().filter(|d| d.is_finite());
+        let near = finite.clone().fold(f32::INFINITY, f32::min);
+        let far = finite.fold(0.0, f32::max);
+        let range = (far - near).max(f32::EPSILON);
+        self.save_channel(path, |index| {
+            let depth = self.depth[index];
+            if !depth.is_finite() {
+                return [0, 0, 0];
+            }
+            let v = (255.0 * (1.0 - (depth - near) / range)).round() as u8;
+            [v, v, v]
+        })
+    }
+
+    // Normales codificadas como color: n * 0.5 + 0.5
+    pub fn save_normals<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.save_channel(path, |index| {
+            let n = self.normal[index];
+            if n == Vec3::zeros() {
+                return [0, 0, 0];
+            }
+            let to_u8 = |v: f32| ((v * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0).round() as u8;
+            [to_u8(n.x), to_u8(n.y), to_u8(n.z)]
+        })
+    }
+
+    // Un color arbitrario pero fijo por objeto; negro para el fondo
+    pub fn save_object_ids<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.save_channel(path, |index| match self.object_id[index] {
+            Some(id) => {
+                let hash = (id.wrapping_add(1)).wrapping_mul(0x9E37_79B1);
+                [(hash >> 24) as u8, (hash >> 16) as u8, (hash >> 8) as u8]
+            }
+            None => [0, 0, 0],
+        })
+    }
+
+    fn save_channel<P: AsRef<Path>>(
+        &self,
+        path: P,
+        pixel: impl Fn(usize) -> [u8; 3],
+    ) -> ImageResult<()> {
+        let image = RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            Rgb(pixel(y as usize * self.width + x as usize))
+        });
+        image.save(path)
+    }
+}
@@ -0,0 +1,699 @@
+// scene.rs
+// Carga de escenas desde un archivo TOML (objetos, materiales, cámara y luces)
+use crate::animation::{CameraAnimation, CameraKeyframe, Interpolation};
+use crate::bvh::Bvh;
+use crate::camera::{Camera, DEFAULT_FOV, Projection};
+use crate::color::{Color, LinearColor};
+use crate::cone::ConeSection;
+use crate::cube::Cube;
+use crate::environment::Environment;
+use crate::light::{Light, LightShape};
+use crate::material::{Material, PbrParams};
+use crate::mesh::Mesh;
+use crate::obj;
+use crate::pawn::Pawn;
+use crate::plane::Plane;
+use crate::ray_intersect::RayIntersect;
+use crate::sphere::Sphere;
+use crate::texture::{FilterMode, Texture, WrapMode};
+use crate::transform::{self, Transform};
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Escena lista para pasarse a `render()`
+pub struct Scene {
+    pub objects: Bvh,
+    pub camera: Camera,
+    pub lights: Vec<Light>,
+    pub environment: Environment,
+    // Recorrido de la cámara para renderizar secuencias (ver --frames)
+    pub animation: Option<CameraAnimation>,
+}
+
+#[derive(Deserialize)]
+struct SceneDesc {
+    camera: CameraDesc,
+    environment: Option<EnvironmentDesc>,
+    animation: Option<AnimationDesc>,
+    #[serde(default)]
+    lights: Vec<LightDesc>,
+    #[serde(default)]
+    objects: Vec<ObjectEntry>,
+}
+
+#[derive(Deserialize)]
+struct CameraDesc {
+    eye: [f32; 3],
+    center: [f32; 3],
+    #[serde(default = "default_up")]
+    up: [f32; 3],
+    // Profundidad de campo: radio de la lente (0 = pinhole) y distancia de enfoque
+    #[serde(default)]
+    aperture: f32,
+    focus_distance: Option<f32>,
+    // Sin `projection`, perspectiva con el FOV por defecto
+    projection: Option<ProjectionDesc>,
+}
+
+//   projection = { type = "perspective", fov = 50.0 }
+//   projection = { type = "orthographic", height = 6.0 }
+//   projection = { type = "isometric" }
+//   projection = { type = "fisheye", fov = 180.0 }
+//   projection = { type = "equirectangular" }
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ProjectionDesc {
+    Perspective {
+        #[serde(default = "default_fov")]
+        fov: f32,
+    },
+    Orthographic {
+        height: Option<f32>,
+    },
+    Isometric {
+        height: Option<f32>,
+    },
+    Fisheye {
+        #[serde(default = "default_fisheye_fov")]
+        fov: f32,
+    },
+    Equirectangular,
+}
+
+// [animation]
+// interpolation = "spline"   # o "linear"
+// loop = true                # el último keyframe repite el primero y cierra el ciclo
+// [[animation.keyframes]]
+// time = 0.0
+// eye = [0.0, 2.0, 7.0]
+// center = [0.0, -0.5, 0.0]
+// (`up` y `fov` son opcionales: por defecto los de [camera])
+#[derive(Deserialize)]
+struct AnimationDesc {
+    #[serde(default)]
+    interpolation: InterpolationDesc,
+    #[serde(default, rename = "loop")]
+    looped: bool,
+    keyframes: Vec<KeyframeDesc>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum InterpolationDesc {
+    Linear,
+    #[default]
+    Spline,
+}
+
+#[derive(Deserialize)]
+struct KeyframeDesc {
+    time: f32,
+    eye: [f32; 3],
+    center: [f32; 3],
+    up: Option<[f32; 3]>,
+    fov: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct LightDesc {
+    position: [f32; 3],
+    color: [u8; 3],
+    intensity: f32,
+    // Sin `shape` la luz es puntual
+    shape: Option<LightShapeDesc>,
+    #[serde(default = "default_light_samples")]
+    samples: u32,
+}
+
+//   shape = { type = "rectangle", u = [1.0, 0.0, 0.0], v = [0.0, 0.0, 1.0] }
+//   shape = { type = "disk", normal = [0.0, -1.0, 0.0], radius = 0.5 }
+//   shape = { type = "sphere", radius = 0.3 }
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LightShapeDesc {
+    Rectangle { u: [f32; 3], v: [f32; 3] },
+    Disk { normal: [f32; 3], radius: f32 },
+    Sphere { radius: f32 },
+}
+
+// Fondo y reflejos del entorno:
+//   environment = { type = "color", color = [4, 12, 36] }
+//   environment = { type = "equirectangular", texture = "../assets/cielo.png", intensity = 1.0 }
+//   [environment] type = "cube_map" + una tabla [environment.faces] con las seis caras
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum EnvironmentDesc {
+    Color {
+        color: [u8; 3],
+    },
+    Equirectangular {
+        texture: TextureDesc,
+        #[serde(default = "default_scale")]
+        intensity: f32,
+    },
+    CubeMap {
+        faces: CubeTexturesDesc,
+        #[serde(default = "default_scale")]
+        intensity: f32,
+    },
+}
+
+// Phong por defecto; con `pbr = { ... }` el material usa el modelo metálico-rugoso
+// y `diffuse` es el color base (specular y albedo se ignoran)
+#[derive(Deserialize)]
+struct MaterialDesc {
+    diffuse: [u8; 3],
+    #[serde(default)]
+    specular: f32,
+    #[serde(default)]
+    albedo: AlbedoDesc,
+    // Vidrio: transparency = 1.0, ior = 1.5
+    transparency: Option<f32>,
+    #[serde(default = "default_ior")]
+    ior: f32,
+    pbr: Option<PbrDesc>,
+    // A lo sumo uno de los dos: normal map en espacio tangente o mapa de alturas
+    normal_map: Option<TextureDesc>,
+    bump_map: Option<TextureDesc>,
+    #[serde(default = "default_bump_strength")]
+    bump_strength: f32,
+}
+
+// albedo = [difuso, especular, reflexión]. Las escenas anteriores usaban un
+// cuarto valor para la refracción; se lee como `transparency`.
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "albedo debe tener 3 valores [difuso, especular, reflexión]"
+)]
+enum AlbedoDesc {
+    Current([f32; 3]),
+    Legacy([f32; 4]),
+}
+
+impl Default for AlbedoDesc {
+    fn default() -> Self {
+        AlbedoDesc::Current([1.0, 0.0, 0.0])
+    }
+}
+
+#[derive(Deserialize)]
+struct PbrDesc {
+    #[serde(default)]
+    metallic: f32,
+    #[serde(default = "default_roughness")]
+    roughness: f32,
+    #[serde(default)]
+    emission: [u8; 3],
+    #[serde(default = "default_scale")]
+    emission_strength: f32,
+}
+
+// Una textura es solo la ruta o una tabla con ruta, filtro y modo de repetición:
+//   texture = "../assets/marmol.png"
+//   texture = { path = "../assets/marmol.png", filter = "trilinear", wrap = "mirror" }
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TextureDesc {
+    Path(String),
+    Detailed {
+        path: String,
+        #[serde(default)]
+        filter: FilterMode,
+        #[serde(default)]
+        wrap: WrapMode,
+    },
+}
+
+// Una textura opcional por cara, en el mismo orden que Cube::textures
+#[derive(Deserialize, Default)]
+struct CubeTexturesDesc {
+    neg_x: Option<TextureDesc>,
+    pos_x: Option<TextureDesc>,
+    neg_y: Option<TextureDesc>,
+    pos_y: Option<TextureDesc>,
+    neg_z: Option<TextureDesc>,
+    pos_z: Option<TextureDesc>,
+}
+
+// Cualquier objeto puede llevar una transformación opcional
+#[derive(Deserialize)]
+struct ObjectEntry {
+    #[serde(flatten)]
+    object: ObjectDesc,
+    transform: Option<TransformDesc>,
+}
+
+// Escala, rotación (grados, ejes X→Y→Z) y traslación. Rotación y escala
+// se aplican alrededor de `pivot`; por defecto, el centro de la caja envolvente.
+#[derive(Deserialize)]
+struct TransformDesc {
+    #[serde(default)]
+    translate: [f32; 3],
+    #[serde(default)]
+    rotate: [f32; 3],
+    #[serde(default = "default_scale3")]
+    scale: [f32; 3],
+    pivot: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ObjectDesc {
+    Cube {
+        min: [f32; 3],
+        max: [f32; 3],
+        material: MaterialDesc,
+        #[serde(default)]
+        textures: CubeTexturesDesc,
+    },
+    Sphere {
+        center: [f32; 3],
+        radius: f32,
+        material: MaterialDesc,
+    },
+    Plane {
+        point: [f32; 3],
+        normal: [f32; 3],
+        material: MaterialDesc,
+        texture: Option<TextureDesc>,
+        #[serde(default = "default_scale")]
+        scale: f32,
+    },
+    ConeSection {
+        apex: [f32; 3],
+        height: f32,
+        r1: f32,
+        r2: f32,
+        material: MaterialDesc,
+        texture: Option<TextureDesc>,
+    },
+    Pawn {
+        base: [f32; 3],
+        scale: f32,
+        material: MaterialDesc,
+    },
+    // Malla OBJ; `material` se usa donde el MTL no define uno
+    Mesh {
+        path: String,
+        material: Option<MaterialDesc>,
+        #[serde(default = "default_scale")]
+        scale: f32,
+        #[serde(default)]
+        translate: [f32; 3],
+    },
+}
+
+fn default_up() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_fov() -> f32 {
+    DEFAULT_FOV
+}
+
+fn default_fisheye_fov() -> f32 {
+    180.0
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_light_samples() -> u32 {
+    16
+}
+
+fn default_roughness() -> f32 {
+    PbrParams::default().roughness
+}
+
+fn default_ior() -> f32 {
+    1.5
+}
+
+fn default_bump_strength() -> f32 {
+    1.0
+}
+
+fn default_scale3() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+impl Scene {
+    /// Lee y construye la escena descrita en `path`.
+    /// Las rutas de texturas se resuelven relativas a la carpeta del archivo.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("No se pudo leer la escena {}: {}", path.display(), e))?;
+        let desc: SceneDesc = toml::from_str(&source)
+            .map_err(|e| format!("Escena inválida {}: {}", path.display(), e))?;
+
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Scene::from_desc(desc, base_dir)
+    }
+
+    fn from_desc(desc: SceneDesc, base_dir: &Path) -> Result<Self, String> {
+        let camera = Camera::new(
+            Vec3::from(desc.camera.eye),
+            Vec3::from(desc.camera.center),
+            Vec3::from(desc.camera.up),
+        )
+        .with_lens(desc.camera.aperture, desc.camera.focus_distance);
+        let camera = match desc.camera.projection {
+            None => camera,
+            Some(projection) => camera.with_projection(match projection {
+                ProjectionDesc::Perspective { fov } => Projection::Perspective { fov },
+                ProjectionDesc::Orthographic { height } => Projection::Orthographic { height },
+                ProjectionDesc::Isometric { height } => Projection::Isometric { height },
+                ProjectionDesc::Fisheye { fov } => Projection::Fisheye { fov },
+                ProjectionDesc::Equirectangular => Projection::Equirectangular,
+            }),
+        };
+
+        let lights = desc
+            .lights
+            .iter()
+            .map(|light| {
+                let point = Light::new(
+                    Vec3::from(light.position),
+                    color(light.color),
+                    light.intensity,
+                );
+                let shape = match light.shape {
+                    None => return point,
+                    Some(LightShapeDesc::Rectangle { u, v }) => LightShape::Rectangle {
+                        u: Vec3::from(u),
+                        v: Vec3::from(v),
+                    },
+                    Some(LightShapeDesc::Disk { normal, radius }) => LightShape::Disk {
+                        normal: Vec3::from(normal).normalize(),
+                        radius,
+                    },
+                    Some(LightShapeDesc::Sphere { radius }) => LightShape::Sphere { radius },
+                };
+                point.with_shape(shape, light.samples)
+            })
+            .collect();
+
+        let mut objects: Vec<Box<dyn RayIntersect>> = Vec::with_capacity(desc.objects.len());
+        for entry in desc.objects {
+            let object = build_object(entry.object, base_dir)?;
+            let object = match entry.transform {
+                Some(transform) => apply_transform(object, &transform)?,
+                None => object,
+            };
+            objects.push(object);
+        }
+
+        let environment = match desc.environment {
+            Some(environment) => build_environment(environment, base_dir)?,
+            None => Environment::default(),
+        };
+
+        let animation = match desc.animation {
+            None => None,
+            Some(animation) => Some(build_animation(animation, &camera)?),
+        };
+
+        Ok(Scene {
+            objects: Bvh::new(objects),
+            camera,
+            lights,
+            environment,
+            animation,
+        })
+    }
+}
+
+fn build_environment(desc: EnvironmentDesc, base_dir: &Path) -> Result<Environment, String> {
+    let environment = match desc {
+        EnvironmentDesc::Color { color: rgb } => Environment::Color(LinearColor::from(color(rgb))),
+        EnvironmentDesc::Equirectangular { texture, intensity } => {
+            let texture = load_texture_desc(base_dir, texture)?;
+            Environment::Equirectangular { texture, intensity }
+        }
+        EnvironmentDesc::CubeMap { faces, intensity } => {
+            let face = |name: &str, desc: Option<TextureDesc>| {
+                let mut texture = load_texture(base_dir, desc)?
+                    .ok_or_else(|| format!("Falta la cara {} del cube map", name))?;
+                // Sin repetir, para que no se vean costuras en los bordes
+                texture.wrap = WrapMode::Clamp;
+                Ok::<_, String>(texture)
+            };
+            let faces = [
+                face("neg_x", faces.neg_x)?,
+                face("pos_x", faces.pos_x)?,
+                face("neg_y", faces.neg_y)?,
+                face("pos_y", faces.pos_y)?,
+                face("neg_z", faces.neg_z)?,
+                face("pos_z", faces.pos_z)?,
+            ];
+            Environment::CubeMap {
+                faces: Box::new(faces),
+                intensity,
+            }
+        }
+    };
+
+    Ok(environment)
+}
+
+fn build_object(desc: ObjectDesc, base_dir: &Path) -> Result<Box<dyn RayIntersect>, String> {
+    let object: Box<dyn RayIntersect> = match desc {
+        ObjectDesc::Cube {
+            min,
+            max,
+            material,
+            textures,
+        } => Box::new(Cube {
+            min: Vec3::from(min),
+            max: Vec3::from(max),
+            material: material.build(base_dir)?,
+            textures: [
+                load_texture(base_dir, textures.neg_x)?.map(Arc::new),
+                load_texture(base_dir, textures.pos_x)?.map(Arc::new),
+                load_texture(base_dir, textures.neg_y)?.map(Arc::new),
+                load_texture(base_dir, textures.pos_y)?.map(Arc::new),
+                load_texture(base_dir, textures.neg_z)?.map(Arc::new),
+                load_texture(base_dir, textures.pos_z)?.map(Arc::new),
+            ],
+        }),
+        ObjectDesc::Sphere {
+            center,
+            radius,
+            material,
+        } => Box::new(Sphere {
+            center: Vec3::from(center),
+            radius,
+            material: material.build(base_dir)?,
+        }),
+        ObjectDesc::Plane {
+            point,
+            normal,
+            material,
+            texture,
+            scale,
+        } => Box::new(Plane {
+            point: Vec3::from(point),
+            normal: Vec3::from(normal).normalize(),
+            material: material.build(base_dir)?,
+            texture: load_texture(base_dir, texture)?.map(Arc::new),
+            scale,
+        }),
+        ObjectDesc::ConeSection {
+            apex,
+            height,
+            r1,
+            r2,
+            material,
+            texture,
+        } => Box::new(ConeSection {
+            apex: Vec3::from(apex),
+            height,
+            r1,
+            r2,
+            material: material.build(base_dir)?,
+            texture: load_texture(base_dir, texture)?.map(Arc::new),
+        }),
+        ObjectDesc::Pawn {
+            base,
+            scale,
+            material,
+        } => Box::new(Pawn::new(
+            Vec3::from(base),
+            scale,
+            material.build(base_dir)?,
+        )),
+        ObjectDesc::Mesh {
+            path,
+            material,
+            scale,
+            translate,
+        } => {
+            let fallback = material
+                .map(|m| m.build(base_dir))
+                .transpose()?
+                .unwrap_or_else(default_mesh_material);
+            let mut triangles = obj::load_obj(base_dir.join(&path), fallback)?;
+
+            // Escala uniforme y luego traslación (las normales no cambian)
+            let translate = Vec3::from(translate);
+            for triangle in &mut triangles {
+                for p in &mut triangle.positions {
+                    *p = *p * scale + translate;
+                }
+            }
+            Box::new(Mesh::new(triangles))
+        }
+    };
+
+    Ok(object)
+}
+
+impl MaterialDesc {
+    fn build(self, base_dir: &Path) -> Result<Material, String> {
+        let (albedo, transparency) = match (self.albedo, self.transparency) {
+            (AlbedoDesc::Current(albedo), transparency) => (albedo, transparency.unwrap_or(0.0)),
+            (AlbedoDesc::Legacy([diffuse, specular, reflection, refraction]), None) => {
+                ([diffuse, specular, reflection], refraction)
+            }
+            (AlbedoDesc::Legacy(_), Some(_)) => {
+                return Err(
+                    "albedo con 4 valores (el cuarto es la transparencia) y transparency a la vez"
+                        .to_string(),
+                );
+            }
+        };
+        let material = match self.pbr {
+            Some(pbr) => Material::pbr(
+                color(self.diffuse),
+                PbrParams {
+                    metallic: pbr.metallic.clamp(0.0, 1.0),
+                    roughness: pbr.roughness.clamp(0.0, 1.0),
+                    emission: LinearColor::from(color(pbr.emission)) * pbr.emission_strength,
+                },
+            ),
+            None => Material::new(color(self.diffuse), self.specular, albedo),
+        }
+        .with_transparency(transparency, self.ior);
+        let normal_map = load_data_texture(base_dir, self.normal_map)?;
+        let bump_map = load_data_texture(base_dir, self.bump_map)?;
+
+        match (normal_map, bump_map) {
+            (Some(_), Some(_)) => {
+                Err("Un material no puede tener normal_map y bump_map a la vez".to_string())
+            }
+            (Some(normal), None) => Ok(material.with_normal_map(Arc::new(normal))),
+            (None, Some(bump)) => Ok(material.with_bump_map(Arc::new(bump), self.bump_strength)),
+            (None, None) => Ok(material),
+        }
+    }
+}
+
+fn apply_transform(
+    object: Box<dyn RayIntersect>,
+    desc: &TransformDesc,
+) -> Result<Box<dyn RayIntersect>, String> {
+    let pivot = match desc.pivot {
+        Some(pivot) => Vec3::from(pivot),
+        None => {
+            let bounds = object.bounding_box();
+            if bounds.is_finite() {
+                bounds.centroid()
+            } else {
+                Vec3::zeros()
+            }
+        }
+    };
+
+    let matrix = transform::compose(
+        Vec3::from(desc.translate),
+        Vec3::from(desc.rotate),
+        Vec3::from(desc.scale),
+        pivot,
+    );
+    let transformed = Transform::new(object, matrix)
+        .ok_or_else(|| "Transformación no invertible (¿escala 0?)".to_string())?;
+
+    Ok(Box::new(transformed))
+}
+
+fn default_mesh_material() -> Material {
+    Material::new(Color::new(200, 200, 200), 50.0, [0.9, 0.3, 0.0])
+}
+
+fn color(rgb: [u8; 3]) -> Color {
+    Color::new(rgb[0], rgb[1], rgb[2])
+}
+
+fn load_texture(base_dir: &Path, desc: Option<TextureDesc>) -> Result<Option<Texture>, String> {
+    desc.map(|desc| load_texture_desc(base_dir, desc))
+        .transpose()
+}
+
+// Normal maps y mapas de alturas: sin decodificar sRGB (ver Texture::load_raw)
+fn load_data_texture(
+    base_dir: &Path,
+    desc: Option<TextureDesc>,
+) -> Result<Option<Texture>, String> {
+    desc.map(|desc| open_texture(base_dir, desc, true))
+        .transpose()
+}
+
+fn load_texture_desc(base_dir: &Path, desc: TextureDesc) -> Result<Texture, String> {
+    open_texture(base_dir, desc, false)
+}
+
+fn open_texture(base_dir: &Path, desc: TextureDesc, raw: bool) -> Result<Texture, String> {
+    let (path, filter, wrap) = match desc {
+        TextureDesc::Path(path) => (path, FilterMode::default(), WrapMode::default()),
+        TextureDesc::Detailed { path, filter, wrap } => (path, filter, wrap),
+    };
+
+    let full_path: PathBuf = base_dir.join(&path);
+    let loaded = if raw {
+        Texture::load_raw(&full_path)
+    } else {
+        Texture::load(&full_path)
+    };
+    let mut texture = loaded.map_err(|e| {
+        format!(
+            "No se pudo cargar la textura {}: {}",
+            full_path.display(),
+            e
+        )
+    })?;
+    texture.filter = filter;
+    texture.wrap = wrap;
+
+    Ok(texture)
+}
+
+// Los keyframes heredan de la cámara de la escena lo que no indiquen
+fn build_animation(desc: AnimationDesc, camera: &Camera) -> Result<CameraAnimation, String> {
+    let camera_fov = match camera.projection {
+        Projection::Perspective { fov } | Projection::Fisheye { fov } => fov,
+        _ => DEFAULT_FOV,
+    };
+    let keyframes = desc
+        .keyframes
+        .into_iter()
+        .map(|key| CameraKeyframe {
+            time: key.time,
+            eye: Vec3::from(key.eye),
+            center: Vec3::from(key.center),
+            up: key.up.map(Vec3::from).unwrap_or(camera.up),
+            fov: key.fov.unwrap_or(camera_fov),
+        })
+        .collect();
+    let interpolation = match desc.interpolation {
+        InterpolationDesc::Linear => Interpolation::Linear,
+        InterpolationDesc::Spline => Interpolation::Spline,
+    };
+    CameraAnimation::new(keyframes, interpolation, desc.looped)
+}
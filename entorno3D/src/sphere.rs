@@ -1,7 +1,9 @@
 //sphere.rs
+use crate::aabb::Aabb;
 use crate::material::Material;
 use crate::ray_intersect::{Intersect, RayIntersect};
-use nalgebra_glm::{Vec3, dot};
+use nalgebra_glm::{Vec2, Vec3, dot};
+use std::f32::consts::PI;
 
 pub struct Sphere {
     pub center: Vec3,
@@ -31,11 +33,25 @@ impl RayIntersect for Sphere {
                 let normal = (point - self.center).normalize();
                 let distance = t;
 
-                return Intersect::new(point, normal, distance, self.material);
+                // UV esféricas: u da la vuelta alrededor de Y, v sube de polo a polo
+                let uv = Vec2::new(
+                    (normal.x.atan2(normal.z) / PI + 1.0) * 0.5,
+                    normal.y.clamp(-1.0, 1.0).asin() / PI + 0.5,
+                );
+                let tangent = Vec3::new(normal.z, 0.0, -normal.x);
+                let bitangent = normal.cross(&tangent);
+
+                return Intersect::new(point, normal, distance, self.material.clone())
+                    .with_surface(uv, tangent, bitangent);
             }
         }
 
         // If no intersection, return an empty intersect
         Intersect::empty()
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let r = Vec3::repeat(self.radius);
+        Aabb::new(self.center - r, self.center + r)
+    }
 }
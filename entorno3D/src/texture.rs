@@ -1,29 +1,237 @@
 // texture.rs
-use crate::color::Color;
-use image::{DynamicImage, GenericImageView};
+use crate::color::{Color, LinearColor};
+use image::{ImageError, RgbImage};
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterMode {
+    #[default]
+    Nearest,
+    Bilinear,
+    Trilinear, // bilineal en los dos mipmaps más cercanos
+}
+
+// Qué hacer con coordenadas UV fuera de [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    #[default]
+    Repeat,
+    Clamp,
+    Mirror,
+}
 
 pub struct Texture {
-    pub image: DynamicImage,
+    // levels[0] es la imagen original; cada nivel siguiente mide la mitad
+    levels: Vec<Level>,
+    pub filter: FilterMode,
+    pub wrap: WrapMode,
+}
+
+// Un nivel de mipmap, ya en lineal: se filtra y promedia sin volver a 8 bits
+struct Level {
+    width: u32,
+    height: u32,
+    texels: Vec<LinearColor>,
+}
+
+impl Level {
+    fn from_image(image: &RgbImage, decode: fn(Color) -> LinearColor) -> Self {
+        Level {
+            width: image.width(),
+            height: image.height(),
+            texels: image
+                .pixels()
+                .map(|p| decode(Color::new(p[0], p[1], p[2])))
+                .collect(),
+        }
+    }
+
+    fn get(&self, x: u32, y: u32) -> LinearColor {
+        self.texels[(y * self.width + x) as usize]
+    }
 }
 
 impl Texture {
     pub fn from_file(path: &str) -> Self {
-        let img = image::open(path).expect("No se pudo cargar la textura");
-        Texture { image: img }
+        Texture::load(path).expect("No se pudo cargar la textura")
+    }
+
+    // Igual que from_file, pero devuelve el error en lugar de entrar en pánico
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let img = image::open(path)?;
+        Ok(Texture::from_image(img.to_rgb8()))
+    }
+
+    /// Como `load`, para texturas que guardan datos y no colores (normal
+    /// maps, mapas de alturas): los canales se leen sin decodificar sRGB
+    pub fn load_raw<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let img = image::open(path)?;
+        Ok(Texture::from_raw_image(img.to_rgb8()))
+    }
+
+    /// Imagen de color en sRGB: se decodifica a lineal al cargar
+    pub fn from_image(image: RgbImage) -> Self {
+        Texture::from_level(Level::from_image(&image, LinearColor::from))
+    }
+
+    pub fn from_raw_image(image: RgbImage) -> Self {
+        Texture::from_level(Level::from_image(&image, LinearColor::from_raw))
+    }
+
+    // La cadena de mipmaps se construye una sola vez, al cargar
+    fn from_level(level: Level) -> Self {
+        let mut levels = vec![level];
+        loop {
+            let last = &levels[levels.len() - 1];
+            if last.width == 1 && last.height == 1 {
+                break;
+            }
+            let next = downsample(last);
+            levels.push(next);
+        }
+
+        Texture {
+            levels,
+            filter: FilterMode::default(),
+            wrap: WrapMode::default(),
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.levels[0].width, self.levels[0].height)
+    }
+
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Color en la resolución completa (sin mipmaps)
+    pub fn get_color(&self, u: f32, v: f32) -> LinearColor {
+        self.sample(u, v, 0.0)
+    }
+
+    /// `footprint`: tamaño que cubre un píxel en unidades UV (1.0 = toda la textura).
+    /// Solo el filtro trilineal lo usa para elegir el nivel de mipmap.
+    pub fn sample(&self, u: f32, v: f32, footprint: f32) -> LinearColor {
+        match self.filter {
+            FilterMode::Nearest => self.nearest(0, u, v),
+            FilterMode::Bilinear => self.bilinear(0, u, v),
+            FilterMode::Trilinear => {
+                let (w, h) = self.dimensions();
+                let texels = footprint * w.max(h) as f32;
+                let lod = texels.max(1.0).log2().min((self.levels.len() - 1) as f32);
+
+                let level = lod.floor() as usize;
+                let t = lod - level as f32;
+                let fine = self.bilinear(level, u, v);
+                if t == 0.0 || level + 1 >= self.levels.len() {
+                    fine
+                } else {
+                    fine * (1.0 - t) + self.bilinear(level + 1, u, v) * t
+                }
+            }
+        }
     }
 
-    pub fn get_color(&self, u: f32, v: f32) -> Color {
-        let (w, h) = self.image.dimensions();
+    // Texel (x, y) con el modo de repetición aplicado
+    fn texel(&self, level: usize, x: i64, y: i64) -> LinearColor {
+        let level = &self.levels[level];
+        let x = wrap(x, level.width as i64, self.wrap);
+        let y = wrap(y, level.height as i64, self.wrap);
+        level.get(x as u32, y as u32)
+    }
 
-        // Nos aseguramos que u,v estén entre 0 y 1
-        let u = u.fract();
-        let v = v.fract();
+    fn nearest(&self, level: usize, u: f32, v: f32) -> LinearColor {
+        let Level {
+            width: w,
+            height: h,
+            ..
+        } = self.levels[level];
+        // v crece hacia arriba, las filas de la imagen hacia abajo
+        let x = (u * w as f32).floor() as i64;
+        let y = ((1.0 - v) * h as f32).floor() as i64;
+        self.texel(level, x, y)
+    }
 
-        // Mapear a coordenadas de la imagen
-        let x = (u * w as f32).clamp(0.0, (w - 1) as f32) as u32;
-        let y = ((1.0 - v) * h as f32).clamp(0.0, (h - 1) as f32) as u32;
+    fn bilinear(&self, level: usize, u: f32, v: f32) -> LinearColor {
+        let Level {
+            width: w,
+            height: h,
+            ..
+        } = self.levels[level];
+        // Los centros de los texels están en (i + 0.5) / w
+        let x = u * w as f32 - 0.5;
+        let y = (1.0 - v) * h as f32 - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
 
-        let pixel = self.image.get_pixel(x, y);
-        Color::new(pixel[0], pixel[1], pixel[2])
+        let top = self.texel(level, x0, y0) * (1.0 - fx) + self.texel(level, x0 + 1, y0) * fx;
+        let bottom =
+            self.texel(level, x0, y0 + 1) * (1.0 - fx) + self.texel(level, x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
     }
 }
+
+// Sin volcar los píxeles: solo lo que sirve para depurar
+impl fmt::Debug for Texture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Texture")
+            .field("dimensions", &self.dimensions())
+            .field("mip_levels", &self.mip_levels())
+            .field("filter", &self.filter)
+            .field("wrap", &self.wrap)
+            .finish()
+    }
+}
+
+fn wrap(i: i64, size: i64, mode: WrapMode) -> i64 {
+    match mode {
+        WrapMode::Repeat => i.rem_euclid(size),
+        WrapMode::Clamp => i.clamp(0, size - 1),
+        WrapMode::Mirror => {
+            let m = i.rem_euclid(2 * size);
+            if m < size { m } else { 2 * size - 1 - m }
+        }
+    }
+}
+
+// Promedio de bloques de 2x2 (con dimensiones impares se repite el borde)
+fn downsample(level: &Level) -> Level {
+    let (w, h) = (level.width, level.height);
+    let nw = (w / 2).max(1);
+    let nh = (h / 2).max(1);
+
+    let mut texels = Vec::with_capacity((nw * nh) as usize);
+    for y in 0..nh {
+        for x in 0..nw {
+            let mut sum = LinearColor::black();
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                sum += level.get((2 * x + dx).min(w - 1), (2 * y + dy).min(h - 1));
+            }
+            texels.push(sum * 0.25);
+        }
+    }
+    Level {
+        width: nw,
+        height: nh,
+        texels,
+    }
+}
+
+/// Unidades UV que cubre un ancho de 1 unidad de mundo (perpendicular al
+/// rayo) en un punto de impacto; multiplicado por el ancho del píxel ahí
+/// (`RayCone::width`) da el `footprint` de `Texture::sample`.
+/// `texture_size` es cuántas unidades de mundo abarca la textura completa.
+pub fn footprint_scale(ray_direction: &Vec3, normal: &Vec3, texture_size: f32) -> f32 {
+    // A ángulos rasantes el píxel se estira sobre la superficie
+    let cos_theta = ray_direction.normalize().dot(normal).abs().max(0.05);
+    1.0 / (cos_theta * texture_size.max(1e-6))
+}
@@ -0,0 +1,105 @@
+// transform.rs
+// Envoltura con una matriz 4x4 alrededor de cualquier RayIntersect.
+// Permite rotar, escalar (también de forma no uniforme) y trasladar primitivas
+// que por sí solas solo se pueden colocar por posición.
+use crate::aabb::Aabb;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::{self as glm, Mat3, Mat4, Vec3};
+
+pub struct Transform {
+    pub object: Box<dyn RayIntersect>,
+    matrix: Mat4,  // objeto -> mundo
+    inverse: Mat4, // mundo -> objeto
+    normal_matrix: Mat3,
+}
+
+impl Transform {
+    /// Devuelve None si la matriz no es invertible (por ejemplo, escala 0)
+    pub fn new(object: Box<dyn RayIntersect>, matrix: Mat4) -> Option<Self> {
+        let inverse = matrix.try_inverse()?;
+        // Las normales se transforman con la inversa transpuesta
+        let normal_matrix = glm::mat4_to_mat3(&inverse).transpose();
+
+        Some(Transform {
+            object,
+            matrix,
+            inverse,
+            normal_matrix,
+        })
+    }
+
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+}
+
+/// Escala, luego rota (ángulos de Euler en grados, primero X, luego Y, luego Z)
+/// y al final traslada. Rotación y escala se aplican alrededor de `pivot`.
+pub fn compose(translate: Vec3, rotate_degrees: Vec3, scale: Vec3, pivot: Vec3) -> Mat4 {
+    let rotation = glm::rotation(rotate_degrees.z.to_radians(), &Vec3::z())
+        * glm::rotation(rotate_degrees.y.to_radians(), &Vec3::y())
+        * glm::rotation(rotate_degrees.x.to_radians(), &Vec3::x());
+
+    glm::translation(&(translate + pivot))
+        * rotation
+        * glm::scaling(&scale)
+        * glm::translation(&-pivot)
+}
+
+fn transform_point(m: &Mat4, p: &Vec3) -> Vec3 {
+    glm::vec4_to_vec3(&(m * glm::vec4(p.x, p.y, p.z, 1.0)))
+}
+
+fn transform_vector(m: &Mat4, v: &Vec3) -> Vec3 {
+    glm::vec4_to_vec3(&(m * glm::vec4(v.x, v.y, v.z, 0.0)))
+}
+
+impl RayIntersect for Transform {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        // Llevamos el rayo al espacio del objeto. La dirección se normaliza
+        // porque algunas primitivas lo asumen; `scale` convierte distancias.
+        let local_origin = transform_point(&self.inverse, ray_origin);
+        let local_direction = transform_vector(&self.inverse, ray_direction);
+        let scale = local_direction.magnitude();
+        if scale == 0.0 {
+            return Intersect::empty();
+        }
+        let local_direction = local_direction / scale;
+
+        let mut hit = self.object.ray_intersect(&local_origin, &local_direction);
+        if !hit.is_intersecting {
+            return hit;
+        }
+
+        // De vuelta al mundo
+        hit.point = transform_point(&self.matrix, &hit.point);
+        hit.normal = (self.normal_matrix * hit.normal).normalize();
+        hit.tangent = transform_vector(&self.matrix, &hit.tangent);
+        hit.bitangent = transform_vector(&self.matrix, &hit.bitangent);
+        hit.distance /= scale;
+        // Un ancho de 1 en el mundo mide `scale` en el espacio del objeto
+        if let Some(texture) = &mut hit.texture {
+            texture.footprint_scale *= scale;
+        }
+        hit
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let local = self.object.bounding_box();
+        if !local.is_finite() {
+            return local;
+        }
+
+        // Caja que contiene las 8 esquinas transformadas
+        let mut bounds = Aabb::empty();
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { local.min.x } else { local.max.x },
+                if i & 2 == 0 { local.min.y } else { local.max.y },
+                if i & 4 == 0 { local.min.z } else { local.max.z },
+            );
+            bounds = bounds.grow(&transform_point(&self.matrix, &corner));
+        }
+        bounds
+    }
+}
@@ -0,0 +1,230 @@
+// bvh.rs
+// El BVH solo descarta objetos con sus cajas envolventes: para cualquier rayo
+// tiene que dar el mismo impacto que probar todos los objetos uno por uno.
+// Si la caja de una primitiva no la contiene entera, este test lo detecta.
+use entorno3d::bvh::Bvh;
+use entorno3d::color::Color;
+use entorno3d::cone::ConeSection;
+use entorno3d::cube::Cube;
+use entorno3d::material::Material;
+use entorno3d::mesh::{Mesh, MeshMaterial, Triangle};
+use entorno3d::pawn::{Frustum, Pawn};
+use entorno3d::plane::Plane;
+use entorno3d::ray_intersect::{Intersect, RayIntersect};
+use entorno3d::sphere::Sphere;
+use entorno3d::transform::{self, Transform};
+use nalgebra_glm::Vec3;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+fn material() -> Material {
+    Material::new(Color::new(200, 200, 200), 10.0, [1.0, 0.0, 0.0])
+}
+
+// Una instancia de cada primitiva
+fn primitives() -> Vec<(&'static str, Box<dyn RayIntersect>)> {
+    let triangle_material = Arc::new(MeshMaterial {
+        material: material(),
+        texture: None,
+    });
+    let triangle = Triangle {
+        positions: [
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.2, 0.3),
+            Vec3::new(0.0, 1.5, -0.4),
+        ],
+        normals: None,
+        uvs: None,
+        material: triangle_material,
+    };
+    let cube = || Cube {
+        min: Vec3::new(-0.5, -0.5, -0.5),
+        max: Vec3::new(0.5, 0.8, 0.5),
+        material: material(),
+        textures: Default::default(),
+    };
+    let matrix = transform::compose(
+        Vec3::new(0.3, 0.1, -0.2),
+        Vec3::new(30.0, 45.0, 10.0),
+        Vec3::new(1.5, 0.7, 1.0),
+        Vec3::zeros(),
+    );
+
+    vec![
+        (
+            "sphere",
+            Box::new(Sphere {
+                center: Vec3::new(0.2, 0.1, -0.3),
+                radius: 0.9,
+                material: material(),
+            }),
+        ),
+        ("cube", Box::new(cube())),
+        (
+            "plane",
+            Box::new(Plane {
+                point: Vec3::new(0.0, -1.0, 0.0),
+                normal: Vec3::y(),
+                material: material(),
+                texture: None,
+                scale: 1.0,
+            }),
+        ),
+        // Cono que se angosta hacia arriba y otro que se ensancha
+        (
+            "cone narrowing",
+            Box::new(ConeSection {
+                apex: Vec3::new(0.0, 0.5, 0.0),
+                height: 1.0,
+                r1: 0.5,
+                r2: 0.2,
+                material: material(),
+                texture: None,
+            }),
+        ),
+        (
+            "cone widening",
+            Box::new(ConeSection {
+                apex: Vec3::new(0.0, 0.5, 0.0),
+                height: 1.5,
+                r1: 0.1,
+                r2: 0.8,
+                material: material(),
+                texture: None,
+            }),
+        ),
+        (
+            "frustum",
+            Box::new(Frustum {
+                base: Vec3::new(0.0, -0.5, 0.0),
+                radius_bottom: 0.6,
+                radius_top: 0.3,
+                height: 1.2,
+                material: material(),
+            }),
+        ),
+        (
+            "pawn",
+            Box::new(Pawn::new(Vec3::new(0.0, -0.8, 0.0), 1.0, material())),
+        ),
+        ("mesh", Box::new(Mesh::new(vec![triangle]))),
+        (
+            "transform",
+            Box::new(Transform::new(Box::new(cube()), matrix).unwrap()),
+        ),
+    ]
+}
+
+fn linear_closest(objects: &[Box<dyn RayIntersect>], origin: &Vec3, direction: &Vec3) -> Intersect {
+    let mut closest = Intersect::empty();
+    for object in objects {
+        let hit = object.ray_intersect(origin, direction);
+        if hit.is_intersecting && (!closest.is_intersecting || hit.distance < closest.distance) {
+            closest = hit;
+        }
+    }
+    closest
+}
+
+// Rayos desde puntos alrededor de la primitiva hacia puntos cerca de ella
+fn random_rays(count: usize) -> Vec<(Vec3, Vec3)> {
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut point = |extent: f32| {
+        Vec3::new(
+            (rng.random::<f32>() * 2.0 - 1.0) * extent,
+            (rng.random::<f32>() * 2.0 - 1.0) * extent,
+            (rng.random::<f32>() * 2.0 - 1.0) * extent,
+        )
+    };
+    (0..count)
+        .map(|_| {
+            let origin = point(5.0);
+            let target = point(1.5);
+            (origin, (target - origin).normalize())
+        })
+        .collect()
+}
+
+fn assert_same_hit(name: &str, bvh: &Intersect, linear: &Intersect, ray: &(Vec3, Vec3)) {
+    assert_eq!(
+        bvh.is_intersecting, linear.is_intersecting,
+        "{}: el BVH y el recorrido lineal no coinciden para el rayo {:?}",
+        name, ray
+    );
+    if linear.is_intersecting {
+        assert!(
+            (bvh.distance - linear.distance).abs() <= 1e-4 * linear.distance.max(1.0),
+            "{}: distancia {} con BVH y {} lineal para el rayo {:?}",
+            name,
+            bvh.distance,
+            linear.distance,
+            ray
+        );
+    }
+}
+
+#[test]
+fn bvh_matches_linear_scan_for_every_primitive() {
+    let rays = random_rays(4000);
+    for (name, object) in primitives() {
+        let objects: Vec<Box<dyn RayIntersect>> = vec![object];
+        let bvh = Bvh::new(objects);
+        let mut hits = 0;
+        for ray in &rays {
+            let expected = linear_closest(bvh.objects(), &ray.0, &ray.1);
+            let actual = bvh.closest_hit(&ray.0, &ray.1);
+            assert_same_hit(name, &actual, &expected, ray);
+            hits += expected.is_intersecting as usize;
+        }
+        assert!(hits > 0, "{}: ningún rayo golpeó la primitiva", name);
+    }
+}
+
+#[test]
+fn bvh_matches_linear_scan_for_a_mixed_scene() {
+    let objects: Vec<Box<dyn RayIntersect>> = primitives()
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, object))| {
+            // Separamos las primitivas para que el árbol tenga varios niveles
+            let offset = Vec3::new((i % 3) as f32 * 1.2 - 1.2, 0.0, (i / 3) as f32 * 1.2 - 1.2);
+            let matrix = transform::compose(
+                offset,
+                Vec3::zeros(),
+                Vec3::new(0.5, 0.5, 0.5),
+                Vec3::zeros(),
+            );
+            Box::new(Transform::new(object, matrix).unwrap()) as Box<dyn RayIntersect>
+        })
+        .collect();
+    let bvh = Bvh::new(objects);
+    for ray in &random_rays(4000) {
+        let expected = linear_closest(bvh.objects(), &ray.0, &ray.1);
+        let actual = bvh.closest_hit(&ray.0, &ray.1);
+        assert_same_hit("escena", &actual, &expected, ray);
+    }
+}
+
+// El caso que el BVH recortaba: r1 = 0.5 en la base, r2 = 0.2 arriba
+#[test]
+fn cone_bounding_box_contains_its_hits() {
+    let cone = ConeSection {
+        apex: Vec3::zeros(),
+        height: 1.0,
+        r1: 0.5,
+        r2: 0.2,
+        material: material(),
+        texture: None,
+    };
+    let bounds = cone.bounding_box();
+    for ray in &random_rays(4000) {
+        let hit = cone.ray_intersect(&ray.0, &ray.1);
+        if hit.is_intersecting {
+            let p = hit.point;
+            let inside =
+                (0..3).all(|a| p[a] >= bounds.min[a] - 1e-4 && p[a] <= bounds.max[a] + 1e-4);
+            assert!(inside, "impacto {:?} fuera de la caja {:?}", p, bounds);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// filter.rs
+// Las muestras estratificadas tienen que cubrir todo el soporte del filtro:
+// si alguna celda de la cuadrícula queda vacía, el promedio del píxel se corre.
+use entorno3d::filter::PixelFilter;
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+#[test]
+fn stratified_samples_are_centered_for_any_count() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    for count in 1..=16 {
+        let rounds = 2000;
+        let (mut sum_x, mut sum_y) = (0.0, 0.0);
+        for _ in 0..rounds {
+            for index in 0..count {
+                let (dx, dy) = PixelFilter::Box.sample_offset(index, count, &mut rng);
+                assert!(dx.abs() <= 0.5 && dy.abs() <= 0.5);
+                sum_x += dx;
+                sum_y += dy;
+            }
+        }
+        let samples = (rounds * count) as f32;
+        let (mean_x, mean_y) = (sum_x / samples, sum_y / samples);
+        assert!(
+            mean_x.abs() < 0.02 && mean_y.abs() < 0.02,
+            "con {} muestras el promedio cae en ({}, {}) en lugar del centro",
+            count,
+            mean_x,
+            mean_y
+        );
+    }
+}
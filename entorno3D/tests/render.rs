@@ -0,0 +1,63 @@
+// render.rs
+// Cada píxel deriva su semilla de la del render: repartir las filas entre
+// hilos no puede cambiar la imagen respecto del render en un solo hilo.
+use entorno3d::framebuffer::Framebuffer;
+use entorno3d::raytracer::{Integrator, RenderSettings, render};
+use entorno3d::scene::Scene;
+
+const WIDTH: usize = 48;
+const HEIGHT: usize = 36;
+
+fn load(name: &str) -> Scene {
+    let path = format!("{}/scenes/{}", env!("CARGO_MANIFEST_DIR"), name);
+    Scene::load(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+fn render_with(scene: &Scene, settings: &RenderSettings) -> Vec<u32> {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    render(&mut framebuffer, scene, settings);
+    framebuffer.buffer
+}
+
+fn assert_parallel_matches_serial(name: &str, integrator: Integrator) {
+    let scene = load(name);
+    let settings = RenderSettings {
+        seed: 11,
+        integrator,
+        // Varias muestras con jitter para que el resultado dependa del RNG
+        samples_per_pixel: 2,
+        jitter: true,
+        ..RenderSettings::default()
+    };
+    let parallel = render_with(
+        &scene,
+        &RenderSettings {
+            parallel: true,
+            ..settings
+        },
+    );
+    let serial = render_with(
+        &scene,
+        &RenderSettings {
+            parallel: false,
+            ..settings
+        },
+    );
+    let differing = parallel.iter().zip(&serial).filter(|(a, b)| a != b).count();
+    assert_eq!(
+        differing, 0,
+        "{} ({:?}): {} píxeles distintos entre el render paralelo y el secuencial",
+        name, integrator, differing
+    );
+}
+
+#[test]
+fn parallel_render_matches_serial_render() {
+    assert_parallel_matches_serial("default.toml", Integrator::Whitted);
+    assert_parallel_matches_serial("pbr.toml", Integrator::Whitted);
+}
+
+#[test]
+fn parallel_path_tracing_matches_serial_path_tracing() {
+    assert_parallel_matches_serial("default.toml", Integrator::PathTracer);
+}
@@ -0,0 +1,119 @@
+// secondary_rays.rs
+// Con un bump map fuerte la normal de sombreado se aleja mucho de la
+// geométrica: los rayos reflejados tienen que seguir del lado por el que llegó
+// el rayo y los refractados cruzar al otro, o vuelven a golpear la misma
+// superficie y cada rebote multiplica los rayos.
+use entorno3d::color::Color;
+use entorno3d::material::Material;
+use entorno3d::plane::Plane;
+use entorno3d::ray_intersect::RayIntersect;
+use entorno3d::raytracer::{keep_reflection_outside, reflect_on_surface, refract_through_surface};
+use entorno3d::texture::Texture;
+use image::{Rgb, RgbImage};
+use nalgebra_glm::Vec3;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+// Plano y = 0 con un mapa de alturas de ruido, a la intensidad de scenes/torre.toml
+fn bumped_plane() -> Plane {
+    let mut rng = SmallRng::seed_from_u64(3);
+    let heights = RgbImage::from_fn(64, 64, |_, _| {
+        let h: u8 = rng.random();
+        Rgb([h, h, h])
+    });
+    let material = Material::new(Color::new(200, 200, 200), 50.0, [1.0, 0.5, 0.15])
+        .with_transparency(0.2, 1.5)
+        .with_bump_map(Arc::new(Texture::from_raw_image(heights)), 6.0);
+    Plane {
+        point: Vec3::zeros(),
+        normal: Vec3::y(),
+        material,
+        texture: None,
+        scale: 1.0,
+    }
+}
+
+// Rayos hacia el plano desde arriba y desde abajo, algunos casi rasantes
+fn rays(count: usize) -> Vec<(Vec3, Vec3)> {
+    let mut rng = SmallRng::seed_from_u64(5);
+    (0..count)
+        .map(|i| {
+            let above = i % 2 == 0;
+            let height = 0.05 + rng.random::<f32>() * 3.0;
+            let origin = Vec3::new(
+                rng.random::<f32>() * 8.0 - 4.0,
+                if above { height } else { -height },
+                rng.random::<f32>() * 8.0 - 4.0,
+            );
+            let target = Vec3::new(rng.random::<f32>() * 2.0 - 1.0, 0.0, rng.random::<f32>());
+            (origin, (target - origin).normalize())
+        })
+        .collect()
+}
+
+#[test]
+fn bumped_surface_keeps_secondary_rays_on_the_right_side() {
+    let plane = bumped_plane();
+    let mut rng = SmallRng::seed_from_u64(9);
+    let mut bent = 0;
+
+    for (origin, direction) in rays(20_000) {
+        let hit = plane.ray_intersect(&origin, &direction);
+        assert!(hit.is_intersecting);
+        let normal = hit.shading_normal();
+        // Normal geométrica del lado por el que llega el rayo
+        let arrival = if direction.dot(&hit.normal) < 0.0 {
+            hit.normal
+        } else {
+            -hit.normal
+        };
+
+        // Reflejo sin corregir: con este relieve a veces cruza la superficie
+        let naive = direction - normal * (2.0 * direction.dot(&normal));
+        if naive.dot(&arrival) <= 0.0 {
+            bent += 1;
+        }
+
+        let reflected = reflect_on_surface(&direction, &normal, &hit.normal);
+        assert!(
+            reflected.dot(&arrival) > 0.0,
+            "reflejo {:?} del lado equivocado para el rayo {:?}",
+            reflected,
+            direction
+        );
+
+        let jitter = Vec3::new(
+            rng.random::<f32>() - 0.5,
+            rng.random::<f32>() - 0.5,
+            rng.random::<f32>() - 0.5,
+        );
+        let jittered = keep_reflection_outside(&direction, &(reflected + jitter), &hit.normal);
+        assert!(
+            jittered.dot(&arrival) >= 0.0,
+            "reflejo con jitter {:?} del lado equivocado para el rayo {:?}",
+            jittered,
+            direction
+        );
+
+        // Como en cast_dielectric: la normal apunta hacia el lado de llegada
+        let (facing, eta) = if direction.dot(&hit.normal) < 0.0 {
+            (normal, 1.0 / 1.5)
+        } else {
+            (-normal, 1.5)
+        };
+        if let Some(refracted) = refract_through_surface(&direction, &facing, &hit.normal, eta) {
+            assert!(
+                refracted.dot(&arrival) < 0.0,
+                "refracción {:?} del lado equivocado para el rayo {:?}",
+                refracted,
+                direction
+            );
+        }
+    }
+
+    assert!(
+        bent > 0,
+        "el relieve nunca desvió un reflejo: la prueba no cubre el caso"
+    );
+}